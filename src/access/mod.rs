@@ -0,0 +1,356 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Access Control
+
+* This Module defines the Middleware that restricts the Send Endpoints to allowed Networks
+* and resolves the Client Address behind trusted Proxies
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "ipnet" must be installed
+*/
+
+use std::fmt;
+use std::net::IpAddr;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{HeaderMap, StatusCode};
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use futures_util::future::{ok, Either, Ready};
+use ipnet::IpNet;
+use log::{debug, warn};
+
+use crate::config::AccessConfig;
+use crate::ResponseData;
+
+//==============================================================================
+// Structure ClientAddress Declaration
+
+/// Structure for the resolved Client Address
+/// It is stored in the Request Extensions by the `AccessFilter` Middleware
+#[derive(Debug, Clone, Copy)]
+pub struct ClientAddress(pub IpAddr);
+
+impl fmt::Display for ClientAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+//==============================================================================
+// Structure AccessError Declaration
+
+/// Structure for rejected Client Addresses
+#[derive(Debug)]
+pub struct AccessError {
+    pub report: String,
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Access denied: '{}'", self.report)
+    }
+}
+
+impl ResponseError for AccessError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::Forbidden().json(ResponseData {
+            title: String::from("Forbidden"),
+            statuscode: 403,
+            page: String::from("Access"),
+            description: self.to_string(),
+        })
+    }
+}
+
+//==============================================================================
+// Structure AccessFilter Declaration
+
+/// Middleware to accept Requests only from the Networks in the `AccessConfig`
+/// The `forwarded_header` is only evaluated when the Request comes from a trusted Proxy
+pub struct AccessFilter {
+    filter: Rc<NetworkFilter>,
+}
+
+pub struct AccessFilterMiddleware<S> {
+    service: S,
+    filter: Rc<NetworkFilter>,
+}
+
+/// Structure for the parsed Networks of the `AccessConfig`
+pub struct NetworkFilter {
+    enabled: bool,
+    allow: Vec<IpNet>,
+    trusted_proxies: Vec<IpNet>,
+    forwarded_header: String,
+}
+
+//==============================================================================
+// Structure AccessFilter Implementation
+
+impl AccessFilter {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(config: &AccessConfig) -> Self {
+        Self {
+            filter: Rc::new(NetworkFilter::from_config(config)),
+        }
+    }
+}
+
+impl<S, B> Transform<S> for AccessFilter
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AccessFilterMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AccessFilterMiddleware {
+            service,
+            filter: self.filter.clone(),
+        })
+    }
+}
+
+impl<S, B> Service for AccessFilterMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let resolved = match req.peer_addr() {
+            Some(peer) => self.filter.resolve_client(peer.ip(), req.headers()),
+            None => Err(AccessError {
+                report: String::from("Client Address is unknown"),
+            }),
+        };
+
+        match resolved {
+            Ok(client) if self.filter.is_allowed(&client) => {
+                debug!("Request '{}': client address '{}'", req.path(), client);
+
+                req.extensions_mut().insert(ClientAddress(client));
+
+                Either::Left(self.service.call(req))
+            }
+            Ok(client) => {
+                let e = AccessError {
+                    report: format!("Client Address '{}' is not allowed", client),
+                };
+
+                warn!("Request '{}': {}", req.path(), e);
+
+                Either::Right(ok(req.error_response(e)))
+            }
+            Err(e) if !self.filter.enabled => {
+                debug!("Request '{}': {}", req.path(), e);
+
+                Either::Left(self.service.call(req))
+            }
+            Err(e) => {
+                warn!("Request '{}': {}", req.path(), e);
+
+                Either::Right(ok(req.error_response(e)))
+            }
+        }
+    }
+}
+
+//==============================================================================
+// Structure NetworkFilter Implementation
+
+impl NetworkFilter {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &AccessConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            allow: parse_networks(&config.allow),
+            trusted_proxies: parse_networks(&config.trusted_proxies),
+            forwarded_header: config.forwarded_header.trim().to_owned(),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn is_allowed(&self, client: &IpAddr) -> bool {
+        !self.enabled || self.allow.iter().any(|net| net.contains(client))
+    }
+
+    pub fn is_trusted(&self, addr: &IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(addr))
+    }
+
+    /// Resolves the Client Address from the Peer Address and the `forwarded_header`
+    /// The Chain of forwarded Addresses is walked from the right
+    /// and the first Address that is not a trusted Proxy is the Client.
+    /// Chains with invalid Nodes are refused
+    pub fn resolve_client(&self, peer: IpAddr, headers: &HeaderMap) -> Result<IpAddr, AccessError> {
+        if !self.is_trusted(&peer) {
+            return Ok(peer);
+        }
+
+        let mut chain = self.forwarded_addresses(headers)?;
+        let mut client = peer;
+
+        while let Some(addr) = chain.pop() {
+            client = addr;
+
+            if !self.is_trusted(&addr) {
+                break;
+            }
+        }
+
+        Ok(client)
+    }
+
+    /// Reads the forwarded Addresses in the Order of the Proxies from the one Header
+    /// which the trusted Proxy sets. Any other Proxy Header can be sent by the Client itself
+    fn forwarded_addresses(&self, headers: &HeaderMap) -> Result<Vec<IpAddr>, AccessError> {
+        let is_forwarded = self.forwarded_header.eq_ignore_ascii_case("Forwarded");
+        let other_header = if is_forwarded {
+            "X-Forwarded-For"
+        } else {
+            "Forwarded"
+        };
+        let access_error = |report: String| AccessError { report };
+        let mut values = Vec::new();
+
+        for value in headers.get_all(self.forwarded_header.as_str()) {
+            values.push(value.to_str().map_err(|_| {
+                access_error(format!("'{}' Header is not ASCII", self.forwarded_header))
+            })?);
+        }
+
+        // The Proxy did not set its Header but the Client sent another one
+        if values.is_empty() && headers.contains_key(other_header) {
+            return Err(access_error(format!(
+                "'{}' Header conflicts with the '{}' Header of the trusted Proxy",
+                other_header, self.forwarded_header
+            )));
+        }
+
+        let nodes = if is_forwarded {
+            values
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(forwarded_node)
+                .collect::<Result<Vec<&str>, String>>()
+                .map_err(access_error)?
+        } else {
+            values
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .collect()
+        };
+
+        nodes
+            .iter()
+            .map(|node| {
+                parse_node(node).ok_or_else(|| {
+                    access_error(format!(
+                        "Node '{}' in the '{}' Header is invalid",
+                        node, self.forwarded_header
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+fn parse_networks(networks: &[String]) -> Vec<IpNet> {
+    networks
+        .iter()
+        .filter_map(|net| {
+            let parsed = IpNet::from_str(net.trim())
+                .ok()
+                .or_else(|| IpAddr::from_str(net.trim()).ok().map(IpNet::from));
+
+            if parsed.is_none() {
+                warn!("Access Config: Network '{}' is invalid", net);
+            }
+
+            parsed
+        })
+        .collect()
+}
+
+/// Returns the `for` Node of a `Forwarded` Element like `for=192.0.2.60;proto=https`
+fn forwarded_node(element: &str) -> Result<&str, String> {
+    let mut nodes = element.split(';').filter_map(|pair| {
+        let mut parts = pair.splitn(2, '=');
+
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) if key.trim().eq_ignore_ascii_case("for") => {
+                Some(value.trim())
+            }
+            _ => None,
+        }
+    });
+
+    match (nodes.next(), nodes.next()) {
+        (Some(node), None) => Ok(node),
+        (Some(_), Some(_)) => Err(format!(
+            "Element '{}' in the 'Forwarded' Header has several 'for' Nodes",
+            element.trim()
+        )),
+        (None, _) => Err(format!(
+            "Element '{}' in the 'Forwarded' Header has no 'for' Node",
+            element.trim()
+        )),
+    }
+}
+
+/// Parses a Node as `192.0.2.60`, `192.0.2.60:4711`, `"[2001:db8::1]:4711"` or `2001:db8::1`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim_matches('"');
+
+    if let Ok(addr) = IpAddr::from_str(node) {
+        return Some(addr);
+    }
+
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest
+            .split(']')
+            .next()
+            .and_then(|addr| IpAddr::from_str(addr).ok());
+    }
+
+    node.rsplitn(2, ':')
+        .last()
+        .and_then(|addr| IpAddr::from_str(addr).ok())
+}
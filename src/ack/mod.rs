@@ -0,0 +1,291 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Alert Links
+
+* This Module defines the signed Acknowledge and Silence Links embedded in Alert Emails
+* and the Persistence of the Acknowledgements
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "hmac" must be installed
+* - The Rust Crate "base64" must be installed
+*/
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use hmac::{Hmac, Mac};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::config::LinkConfig;
+use crate::email::{EmailData, EmailResponse};
+use crate::store;
+
+const ACK_FILE: &str = "acks.json";
+
+//==============================================================================
+// Structure Acknowledgement Declaration
+
+/// Structure for the Acknowledgement of an Alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    pub fingerprint: String,
+    pub acknowledged_at: u64,
+    pub expires_at: u64,
+}
+
+/// Structure for the persisted Acknowledgements
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AckState {
+    pub acks: BTreeMap<String, Acknowledgement>,
+}
+
+/// Structure for a verified Link Token
+#[derive(Debug, Clone)]
+pub struct LinkToken {
+    pub action: String,
+    pub fingerprint: String,
+    pub expires_at: u64,
+}
+
+//==============================================================================
+// Structure AlertLinks Declaration
+
+/// Structure for the shared Alert Links of all HTTP Workers
+#[derive(Clone)]
+pub struct AlertLinks {
+    config: LinkConfig,
+    file: PathBuf,
+    state: Arc<Mutex<AckState>>,
+}
+
+//==============================================================================
+// Structure AlertLinks Implementation
+
+impl AlertLinks {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Loads the Acknowledgements from the Data Directory
+    /// Durations which cannot be added to the current Time are rejected
+    pub fn from_config(config: &LinkConfig, data_directory: &str) -> Result<Self, Error> {
+        let now = store::unix_time();
+
+        for (name, duration) in &[
+            ("expiry", config.expiry),
+            ("ack_duration", config.ack_duration),
+            ("silence_duration", config.silence_duration),
+        ] {
+            if now.checked_add(*duration).is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Alert Links: '{}' of {}s is too long", name, duration),
+                ));
+            }
+        }
+
+        let file = store::data_file(data_directory, ACK_FILE);
+        let state: AckState = store::load(&file)?;
+
+        Ok(Self {
+            config: config.clone(),
+            file,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Appends the Acknowledge and Silence Links to the Message of the Email
+    pub fn decorate(&self, email: &mut EmailData, fingerprint: &str) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let base_url = self.config.base_url.trim_end_matches('/');
+
+        email.message = format!(
+            "{}\n\n--\nAcknowledge: {}/alert/ack?token={}\nSilence {}: {}/alert/silence?token={}\n",
+            email.message.trim_end(),
+            base_url,
+            self.sign("ack", fingerprint),
+            duration_label(self.config.silence_duration),
+            base_url,
+            self.sign("silence", fingerprint)
+        );
+    }
+
+    /// Records the Acknowledgement of the Alert
+    pub fn acknowledge(&self, fingerprint: &str) -> Acknowledgement {
+        let now = store::unix_time();
+        let ack = Acknowledgement {
+            fingerprint: fingerprint.to_owned(),
+            acknowledged_at: now,
+            expires_at: now
+                .checked_add(self.config.ack_duration)
+                .unwrap_or(u64::MAX),
+        };
+        let mut state = self.lock();
+
+        // Expired Acknowledgements are removed when a new one is recorded
+        state.acks.retain(|_, a| now < a.expires_at);
+        state.acks.insert(fingerprint.to_owned(), ack.clone());
+
+        if let Err(e) = store::save(&self.file, &*state) {
+            error!("Acknowledgements: save to {:?} failed: '{}'", self.file, e);
+        }
+
+        ack
+    }
+
+    /// Checks whether Repeats of an acknowledged Alert are suppressed
+    pub fn check(&self, email: &EmailData, fingerprint: &str) -> Option<EmailResponse> {
+        if !self.config.enabled || !self.config.suppress_acknowledged {
+            return None;
+        }
+
+        let ack = self.acknowledgement(fingerprint)?;
+
+        Some(EmailResponse {
+            status: String::from("acknowledged"),
+            report: format!(
+                "Alert '{}' was acknowledged at {}",
+                fingerprint, ack.acknowledged_at
+            ),
+            job: email.job.clone(),
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn silence_duration(&self) -> u64 {
+        self.config.silence_duration
+    }
+
+    /// Returns the Acknowledgement of the Alert if it has not expired
+    pub fn acknowledgement(&self, fingerprint: &str) -> Option<Acknowledgement> {
+        let now = store::unix_time();
+
+        self.lock()
+            .acks
+            .get(fingerprint)
+            .filter(|ack| now < ack.expires_at)
+            .cloned()
+    }
+
+    /// Creates the Token for the Action on the Alert
+    /// The Token is `<payload>.<signature>` with the Payload `<action>:<expiry>:<fingerprint>`
+    pub fn sign(&self, action: &str, fingerprint: &str) -> String {
+        let expires_at = store::unix_time()
+            .checked_add(self.config.expiry)
+            .unwrap_or(u64::MAX);
+        let payload = base64::encode_config(
+            format!("{}:{}:{}", action, expires_at, fingerprint),
+            base64::URL_SAFE_NO_PAD,
+        );
+
+        format!("{}.{}", payload, hex::encode(self.signature(&payload)))
+    }
+
+    /// Verifies the Signature and the Expiry of the Token
+    pub fn verify(&self, token: &str) -> Result<LinkToken, String> {
+        let mut parts = token.splitn(2, '.');
+        let (payload, signature) = match (parts.next(), parts.next()) {
+            (Some(payload), Some(signature)) => (payload, signature),
+            _ => return Err(String::from("Token is malformed")),
+        };
+        let signature = hex::decode(signature).map_err(|_| String::from("Token is malformed"))?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.config.secret.as_bytes())
+            .map_err(|e| format!("Link Secret is invalid: '{}'", e))?;
+
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| String::from("Token Signature does not match"))?;
+
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| String::from("Token is malformed"))?;
+        let mut fields = payload.splitn(3, ':');
+
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(action), Some(expires_at), Some(fingerprint)) => {
+                let expires_at = expires_at
+                    .parse::<u64>()
+                    .map_err(|_| String::from("Token is malformed"))?;
+
+                if expires_at <= store::unix_time() {
+                    return Err(String::from("Token has expired"));
+                }
+
+                Ok(LinkToken {
+                    action: action.to_owned(),
+                    fingerprint: fingerprint.to_owned(),
+                    expires_at,
+                })
+            }
+            _ => Err(String::from("Token is malformed")),
+        }
+    }
+
+    fn signature(&self, payload: &str) -> Vec<u8> {
+        match Hmac::<Sha256>::new_from_slice(self.config.secret.as_bytes()) {
+            Ok(mut mac) => {
+                mac.update(payload.as_bytes());
+                mac.finalize().into_bytes().to_vec()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AckState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Builds the small Confirmation Page for a clicked Link
+pub fn confirmation_page(title: &str, message: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{0}</title></head>\n\
+         <body>\n<h1>{0}</h1>\n<p>{1}</p>\n</body>\n</html>\n",
+        escape_html(title),
+        escape_html(message)
+    )
+}
+
+/// Formats the Duration in whole Hours or else in Minutes
+fn duration_label(seconds: u64) -> String {
+    if seconds >= 3600 && seconds % 3600 == 0 {
+        format!("{}h", seconds / 3600)
+    } else {
+        format!("{}m", seconds / 60 + u64::from(seconds % 60 != 0))
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,314 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Authentication
+
+* This Module defines the Middleware that authenticates Callers of the Send Endpoints
+* and the Verification of signed Webhook Bodies
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "sha2" must be installed
+* - The Rust Crate "hmac" must be installed
+* - The Rust Crate "base64" must be installed
+*/
+
+use std::fmt;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, HeaderMap, StatusCode};
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use futures_util::future::{ok, Either, Ready};
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+
+use crate::config::{AuthConfig, SignatureConfig};
+use crate::ResponseData;
+
+//==============================================================================
+// Structure Caller Declaration
+
+/// Structure for the authenticated Caller
+/// It is stored in the Request Extensions by the `Authentication` Middleware
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub name: String,
+}
+
+//==============================================================================
+// Structure AuthError Declaration
+
+/// Structure for Authentication Errors
+#[derive(Debug)]
+pub struct AuthError {
+    pub report: String,
+    pub basic_auth: bool,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Authentication failed: '{}'", self.report)
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::Unauthorized();
+
+        if self.basic_auth {
+            response.header(
+                header::WWW_AUTHENTICATE,
+                "Basic realm=\"Actix Alerting Email\"",
+            );
+        }
+
+        response.json(ResponseData {
+            title: String::from("Unauthorized"),
+            statuscode: 401,
+            page: String::from("Authentication"),
+            description: self.to_string(),
+        })
+    }
+}
+
+//==============================================================================
+// Structure Authentication Declaration
+
+/// Middleware to authenticate Requests with the API Keys from the `AuthConfig`
+/// The Keys can be sent as `Authorization: Bearer <key>` header, as `X-API-Key` header
+/// or as HTTP Basic Auth with the Key Name as User and the Key as Password
+pub struct Authentication {
+    config: Rc<AuthConfig>,
+}
+
+pub struct AuthenticationMiddleware<S> {
+    service: S,
+    config: Rc<AuthConfig>,
+}
+
+//==============================================================================
+// Structure Authentication Implementation
+
+impl Authentication {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(config: &AuthConfig) -> Self {
+        Self {
+            config: Rc::new(config.clone()),
+        }
+    }
+}
+
+impl<S, B> Transform<S> for Authentication
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuthenticationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuthenticationMiddleware {
+            service,
+            config: self.config.clone(),
+        })
+    }
+}
+
+impl<S, B> Service for AuthenticationMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        if !self.config.enabled {
+            return Either::Left(self.service.call(req));
+        }
+
+        match authenticate(&self.config, req.headers()) {
+            Ok(caller) => {
+                info!(
+                    "Request '{}': authenticated as '{}'",
+                    req.path(),
+                    caller.name
+                );
+
+                req.extensions_mut().insert(caller);
+
+                Either::Left(self.service.call(req))
+            }
+            Err(e) => {
+                warn!("Request '{}': {}", req.path(), e);
+
+                Either::Right(ok(req.error_response(e)))
+            }
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Computes the SHA-256 Hash of an API Key as it is stored in the `AuthConfig`
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Checks the Request Headers against the configured API Keys
+pub fn authenticate(config: &AuthConfig, headers: &HeaderMap) -> Result<Caller, AuthError> {
+    let auth_error = |report: &str| AuthError {
+        report: report.to_owned(),
+        basic_auth: config.basic_auth,
+    };
+
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    let api_key = headers
+        .get("X-API-Key")
+        .and_then(|value| value.to_str().ok());
+
+    match (authorization, api_key) {
+        (Some(auth), _) if auth.starts_with("Bearer ") => {
+            find_key(config, None, auth["Bearer ".len()..].trim())
+                .ok_or_else(|| auth_error("API Key is invalid"))
+        }
+        (Some(auth), _) if auth.starts_with("Basic ") => {
+            if !config.basic_auth {
+                return Err(auth_error("Basic Authentication is not enabled"));
+            }
+
+            let credentials = base64::decode(auth["Basic ".len()..].trim())
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| auth_error("Basic Credentials are malformed"))?;
+
+            let mut parts = credentials.splitn(2, ':');
+
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(key)) => find_key(config, Some(name), key)
+                    .ok_or_else(|| auth_error("Credentials are invalid")),
+                _ => Err(auth_error("Basic Credentials are malformed")),
+            }
+        }
+        (None, Some(key)) => {
+            find_key(config, None, key.trim()).ok_or_else(|| auth_error("API Key is invalid"))
+        }
+        (Some(_), _) => Err(auth_error("Authorization Scheme is not supported")),
+        (None, None) => Err(auth_error("Credentials are missing")),
+    }
+}
+
+/// Verifies the HMAC-SHA256 Signature of the raw Request Body
+/// The Signature is accepted as hex String with or without the `sha256=` prefix
+pub fn verify_signature(
+    config: &SignatureConfig,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), AuthError> {
+    let auth_error = |report: String| AuthError {
+        report,
+        basic_auth: false,
+    };
+
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let signature = headers
+        .get(config.header.as_str())
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().trim_start_matches("sha256="))
+        .ok_or_else(|| auth_error(format!("Signature Header '{}' is missing", config.header)))?;
+    let signature = hex::decode(signature)
+        .map_err(|_| auth_error(String::from("Signature is not a hex String")))?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(config.secret.as_bytes())
+        .map_err(|e| auth_error(format!("Signature Secret is invalid: '{}'", e)))?;
+
+    if !config.timestamp_header.is_empty() {
+        let timestamp = headers
+            .get(config.timestamp_header.as_str())
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                auth_error(format!(
+                    "Timestamp Header '{}' is missing",
+                    config.timestamp_header
+                ))
+            })?;
+        let sent = timestamp
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| auth_error(String::from("Timestamp is not a Unix Timestamp")))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let skew = if now > sent { now - sent } else { sent - now };
+
+        // Reject replayed Requests outside of the Tolerance Window
+        if skew > config.tolerance {
+            return Err(auth_error(format!(
+                "Timestamp '{}' is outside of the Tolerance of {}s",
+                sent, config.tolerance
+            )));
+        }
+
+        mac.update(timestamp.trim().as_bytes());
+        mac.update(b":");
+    }
+
+    mac.update(body);
+
+    mac.verify_slice(&signature)
+        .map_err(|_| auth_error(String::from("Signature does not match")))
+}
+
+fn find_key(config: &AuthConfig, name: Option<&str>, key: &str) -> Option<Caller> {
+    let key_hash = hash_key(key);
+
+    config
+        .keys
+        .iter()
+        .filter(|k| name.is_none() || name == Some(k.name.as_str()))
+        .find(|k| constant_time_eq(k.hash.to_lowercase().as_bytes(), key_hash.as_bytes()))
+        .map(|k| Caller {
+            name: k.name.clone(),
+        })
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+
+    left.iter()
+        .zip(right.iter())
+        .fold(0u8, |diff, (l, r)| diff | (l ^ r))
+        == 0
+}
@@ -0,0 +1,316 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Circuit Breaker
+
+* This Module defines the Circuit Breaker that stops sending to the SMTP Relay
+* while it is failing and lets single Probe Emails through at Intervals
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::CircuitConfig;
+use crate::metrics::Metrics;
+
+//==============================================================================
+// Structure CircuitBreaker Declaration
+
+/// The States of the Circuit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+    /// Emails are sent to the Relay
+    Closed,
+    /// Emails are refused or queued until the `open_timeout` has passed
+    Open,
+    /// A single Probe Email is sent to the Relay
+    HalfOpen,
+}
+
+/// Structure for the reported State of the Circuit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitStatus {
+    pub enabled: bool,
+    /// `closed`, `open` or `half_open`
+    pub state: String,
+    /// Relay Failures in a Row
+    pub failures: u32,
+    /// Seconds until the next Probe Email is let through
+    pub retry_after: u64,
+}
+
+/// Structure for the Circuit Breaker shared by all Users of the Email Link
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: CircuitConfig,
+    state: Arc<Mutex<BreakerState>>,
+    metrics: Option<Metrics>,
+}
+
+/// Structure for the Permission to send one Email to the Relay
+/// When it holds the Probe and is dropped before the Outcome was recorded
+/// the Probe is released so that the Circuit does not stay half-open
+pub struct CircuitPermit {
+    circuit: CircuitBreaker,
+    probe: bool,
+}
+
+struct BreakerState {
+    circuit: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+}
+
+//==============================================================================
+// Enum CircuitState Implementation
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+
+    /// Value of the State in the Metrics
+    pub fn as_gauge(&self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+//==============================================================================
+// Structure CircuitBreaker Implementation
+
+impl CircuitBreaker {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &CircuitConfig) -> Self {
+        Self {
+            config: config.clone(),
+            state: Arc::new(Mutex::new(BreakerState {
+                circuit: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+            })),
+            metrics: None,
+        }
+    }
+
+    /// Reports the State Changes to the Metrics
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.metrics = Some(metrics.clone());
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Asks whether an Email may be sent to the Relay
+    /// While the Circuit is open the Time until the next Probe is returned
+    pub fn acquire(&self) -> Result<(), Duration> {
+        self.try_acquire().map(|_| ())
+    }
+
+    /// Asks like `acquire()` and returns the Permit which records the Outcome of the Email
+    pub fn enter(&self) -> Result<CircuitPermit, Duration> {
+        let probe = self.try_acquire()?;
+
+        Ok(CircuitPermit {
+            circuit: self.clone(),
+            probe,
+        })
+    }
+
+    /// Returns whether the Email is sent as Probe
+    fn try_acquire(&self) -> Result<bool, Duration> {
+        if !self.config.enabled {
+            return Ok(false);
+        }
+
+        let mut state = self.lock();
+
+        match state.circuit {
+            CircuitState::Closed => Ok(false),
+            CircuitState::Open => {
+                let elapsed = state.opened_at.elapsed();
+                let timeout = self.open_timeout();
+
+                if elapsed >= timeout {
+                    info!("Circuit Breaker: sending a Probe Email to the SMTP Relay ...");
+                    self.transition(&mut state, CircuitState::HalfOpen);
+
+                    Ok(true)
+                } else {
+                    Err(timeout - elapsed)
+                }
+            }
+            // Only the one Probe Email is let through
+            CircuitState::HalfOpen => Err(self.open_timeout()),
+        }
+    }
+
+    /// Records that the Relay accepted the Email
+    /// Returns `true` if this closed the Circuit
+    pub fn record_success(&self) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+
+        let mut state = self.lock();
+
+        state.failures = 0;
+
+        if state.circuit != CircuitState::Closed {
+            info!("Circuit Breaker: SMTP Relay is back; Circuit is closed");
+            self.transition(&mut state, CircuitState::Closed);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Records that the Relay could not be reached or refused the Email
+    pub fn record_failure(&self) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut state = self.lock();
+
+        state.failures += 1;
+
+        let trips = match state.circuit {
+            CircuitState::Closed => state.failures >= self.config.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+
+        if trips {
+            warn!(
+                "Circuit Breaker: SMTP Relay failed {} Times in a Row; Circuit is open for {}s",
+                state.failures, self.config.open_timeout
+            );
+
+            state.opened_at = Instant::now();
+            self.transition(&mut state, CircuitState::Open);
+
+            if let Some(metrics) = &self.metrics {
+                metrics.observe_circuit_open();
+            }
+        }
+    }
+
+    /// Releases the Probe when the Email failed before it reached the Relay
+    /// so that the next Email is sent as Probe
+    pub fn release(&self) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut state = self.lock();
+
+        if state.circuit == CircuitState::HalfOpen {
+            self.transition(&mut state, CircuitState::Open);
+        }
+    }
+
+    fn transition(&self, state: &mut BreakerState, circuit: CircuitState) {
+        state.circuit = circuit;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.set_circuit_state(circuit.as_gauge());
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Tells whether Emails are kept in the Outbox while the Circuit is open
+    pub fn is_queueing(&self) -> bool {
+        self.config.mode == "queue"
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.lock().circuit
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        let state = self.lock();
+        let retry_after = match state.circuit {
+            CircuitState::Open => self
+                .open_timeout()
+                .checked_sub(state.opened_at.elapsed())
+                .map(|wait| wait.as_secs() + 1)
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        CircuitStatus {
+            enabled: self.config.enabled,
+            state: String::from(state.circuit.as_str()),
+            failures: state.failures,
+            retry_after,
+        }
+    }
+
+    fn open_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.open_timeout)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+//==============================================================================
+// Structure CircuitPermit Implementation
+
+impl CircuitPermit {
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Records that the Relay accepted the Email
+    /// Returns `true` if this closed the Circuit
+    pub fn record_success(mut self) -> bool {
+        self.probe = false;
+        self.circuit.record_success()
+    }
+
+    /// Records that the Relay could not be reached or refused the Email
+    pub fn record_failure(mut self) {
+        self.probe = false;
+        self.circuit.record_failure();
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.probe {
+            self.circuit.release();
+        }
+    }
+}
@@ -1,6 +1,6 @@
 /*
 * @author Bodo (Hugo) Barwich
-* @version 2024-01-31
+* @version 2026-10-18
 * @package Grafana Alerting
 * @subpackage Configuration Loader
 
@@ -13,6 +13,7 @@
 extern crate serde;
 extern crate serde_yaml;
 
+use log::{debug, error, warn};
 use serde_derive::{Deserialize, Serialize};
 use std::ffi::OsStr;
 use std::fmt;
@@ -26,6 +27,9 @@ const CONFIG_FILE: &'static str = ".env";
 // Structure SMTPConfig Declaration
 
 /// Structure for the SMTP Configuration
+/// The Timeouts are given in Seconds. `command_timeout` limits each SMTP Command,
+/// `connect_timeout` the Connection of the Relay Probe and `send_timeout` the whole Email.
+/// Without `helo_name` the Host Name is sent in the EHLO Command
 #[derive(Serialize, Deserialize)]
 pub struct SMTPConfig {
     pub host: String,
@@ -34,6 +38,350 @@ pub struct SMTPConfig {
     pub password: String,
     pub full_name: String,
     pub email_address: String,
+    #[serde(default)]
+    pub helo_name: String,
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout: u64,
+    #[serde(default = "default_command_timeout")]
+    pub command_timeout: u64,
+    #[serde(default = "default_send_timeout")]
+    pub send_timeout: u64,
+    #[serde(default)]
+    pub dkim: DKIMConfig,
+    #[serde(default)]
+    pub headers: Vec<HeaderConfig>,
+}
+
+/// Structure for an extra Header added to each Email like `X-Priority` or `Importance`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderConfig {
+    pub name: String,
+    pub value: String,
+}
+
+//==============================================================================
+// Structure DKIMConfig Declaration
+
+/// Structure for the DKIM Signing Configuration
+/// The `private_key` File holds an RSA or an Ed25519 Key in PEM Format.
+/// The Signature covers the `headers` which are present in the Message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DKIMConfig {
+    pub enabled: bool,
+    pub selector: String,
+    pub domain: String,
+    pub private_key: String,
+    pub headers: Vec<String>,
+}
+
+//==============================================================================
+// Structure TLSConfig Declaration
+
+/// Structure for the TLS Listener Configuration
+/// A non-empty `client_ca` File enables the Verification of Client Certificates.
+/// Clients without a Certificate are only refused with `require_client_cert`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TLSConfig {
+    pub enabled: bool,
+    pub certificate: String,
+    pub key: String,
+    pub client_ca: String,
+    #[serde(default)]
+    pub require_client_cert: bool,
+}
+
+//==============================================================================
+// Structure AccessConfig Declaration
+
+/// Structure for the Access Control Configuration
+/// Networks are given in CIDR Notation like `10.0.0.0/8` or as single Addresses.
+/// `forwarded_header` names the one Header which the trusted Proxies set:
+/// `X-Forwarded-For` or `Forwarded`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessConfig {
+    pub enabled: bool,
+    pub allow: Vec<String>,
+    pub trusted_proxies: Vec<String>,
+    #[serde(default = "default_forwarded_header")]
+    pub forwarded_header: String,
+}
+
+//==============================================================================
+// Structure RateLimitConfig Declaration
+
+/// Structure for a Token Bucket Configuration
+/// A `capacity` of 0 disables the Bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketConfig {
+    pub capacity: u32,
+    pub per_minute: u32,
+}
+
+/// Structure for the Rate Limit Configuration
+/// In `refuse` Mode exceeded Requests get a 429 Response, in `queue` Mode
+/// they wait up to `max_queue_wait` Seconds for the next free Token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub mode: String,
+    pub max_queue_wait: u64,
+    pub global: BucketConfig,
+    pub per_recipient: BucketConfig,
+    pub per_caller: BucketConfig,
+}
+
+//==============================================================================
+// Structure DedupConfig Declaration
+
+/// Structure for the Deduplication Configuration
+/// In `drop` Mode Duplicates within the `window` Seconds are discarded,
+/// in `merge` Mode their Count is reported with the next Email of the same Alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupConfig {
+    pub enabled: bool,
+    pub window: u64,
+    pub action: String,
+}
+
+//==============================================================================
+// Structure DigestConfig Declaration
+
+/// Structure for the Digest Configuration
+/// Digests are sent every `interval` Minutes or when `max_items` Alerts are collected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    pub interval: u64,
+    pub max_items: usize,
+}
+
+//==============================================================================
+// Structure LinkConfig Declaration
+
+/// Structure for the Acknowledge and Silence Links in Alert Emails
+/// The Links point to `base_url` and carry a Token signed with `secret`
+/// that expires after `expiry` Seconds
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LinkConfig {
+    pub enabled: bool,
+    pub base_url: String,
+    pub secret: String,
+    pub expiry: u64,
+    pub silence_duration: u64,
+    pub ack_duration: u64,
+    pub suppress_acknowledged: bool,
+}
+
+//==============================================================================
+// Structure RoutingConfig Declaration
+
+/// Structure for a Route Matcher
+/// The Matcher compares a Label, the `fingerprint` or the Fields `subject`, `from`, `to` and `message`
+/// with the `value` or with the `regex` if it is given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatcherConfig {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub regex: String,
+}
+
+/// Structure for the Sender Identity of a Route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderConfig {
+    pub full_name: String,
+    pub email_address: String,
+}
+
+/// Structure for a Route of the Routing Tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteConfig {
+    pub name: String,
+    #[serde(default)]
+    pub matchers: Vec<MatcherConfig>,
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    #[serde(default)]
+    pub sender: Option<SenderConfig>,
+    #[serde(default)]
+    pub subject_template: String,
+    #[serde(default)]
+    pub message_template: String,
+    #[serde(default)]
+    pub digest: Option<bool>,
+    #[serde(default)]
+    pub routes: Vec<RouteConfig>,
+}
+
+/// Structure for the Routing Configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    pub enabled: bool,
+    pub root: RouteConfig,
+}
+
+//==============================================================================
+// Structure EscalationConfig Declaration
+
+/// Structure for a Stage of an Escalation Policy
+/// The Stage is emailed `delay` Seconds after the previous Stage or the original Alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationStageConfig {
+    pub recipients: Vec<String>,
+    pub delay: u64,
+}
+
+/// Structure for an Escalation Policy
+/// The first Policy whose Matchers all match an Alert escalates it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationPolicyConfig {
+    pub name: String,
+    #[serde(default)]
+    pub matchers: Vec<MatcherConfig>,
+    pub stages: Vec<EscalationStageConfig>,
+}
+
+/// Structure for the Escalation Configuration
+/// Pending Escalations are checked every `check_interval` Seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    pub enabled: bool,
+    pub check_interval: u64,
+    pub policies: Vec<EscalationPolicyConfig>,
+}
+
+//==============================================================================
+// Structure ThreadingConfig Declaration
+
+/// Structure for the Email Threading Configuration
+/// The Message-IDs are generated in the `domain` or else in the Domain of the Sender Address.
+/// At most `max_threads` Threads are remembered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadingConfig {
+    pub enabled: bool,
+    pub domain: String,
+    pub max_threads: usize,
+}
+
+//==============================================================================
+// Structure ValidationConfig Declaration
+
+/// Structure for the Validation Limits of incoming Emails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    pub max_subject_length: usize,
+    pub max_header_length: usize,
+}
+
+//==============================================================================
+// Structure HealthConfig Declaration
+
+/// Structure for the Health Checks Configuration
+/// The Readiness is cached for `cache_ttl` Seconds
+/// and each Check must answer within `timeout` Seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    pub cache_ttl: u64,
+    pub timeout: u64,
+}
+
+//==============================================================================
+// Structure MailboxConfig Declaration
+
+/// Structure for the Mailbox of the Email Workers
+/// At most `capacity` Emails wait for or are processed by the Email Workers where 0 means no Limit.
+/// `overflow` is `refuse` to answer with 503 and `Retry-After` or `outbox` to keep the Emails in the Outbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailboxConfig {
+    pub capacity: usize,
+    pub overflow: String,
+    pub retry_after: u64,
+}
+
+//==============================================================================
+// Structure CircuitConfig Declaration
+
+/// Structure for the Circuit Breaker around the SMTP Relay
+/// After `failure_threshold` Relay Failures in a Row the Circuit opens for `open_timeout` Seconds.
+/// `mode` is `refuse` to fail fast or `queue` to keep the Emails in the Outbox meanwhile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitConfig {
+    pub enabled: bool,
+    pub failure_threshold: u32,
+    pub open_timeout: u64,
+    pub mode: String,
+}
+
+//==============================================================================
+// Structure OutboxConfig Declaration
+
+/// Structure for the persistent Outbox Configuration
+/// Emails which could not be sent before the Shutdown are kept in the Data Directory
+/// and sent again when the Service starts. Emails which were kept while the Service runs
+/// are sent again every `interval` Seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxConfig {
+    pub enabled: bool,
+    #[serde(default = "default_outbox_interval")]
+    pub interval: u64,
+}
+
+//==============================================================================
+// Structure ShutdownConfig Declaration
+
+/// Structure for the Shutdown Configuration
+/// Queued and sending Emails are awaited for at most `drain_timeout` Seconds
+/// after the Process received SIGTERM or SIGINT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownConfig {
+    pub drain_timeout: u64,
+}
+
+//==============================================================================
+// Structure LogConfig Declaration
+
+/// Structure for the Logging Configuration
+/// `level` takes Filters like `info` or `info,actix_web=warn`
+/// and `format` is `json` or `text`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogConfig {
+    pub level: String,
+    pub format: String,
+    pub log_bodies: bool,
+}
+
+//==============================================================================
+// Structure AuthConfig Declaration
+
+/// Structure for a single API Key
+/// The Key itself is not stored but only its SHA-256 Hash as hex String
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub hash: String,
+}
+
+/// Structure for the Webhook Signature Configuration
+/// The HMAC-SHA256 Signature is computed over `<timestamp>:<body>`
+/// or over the Body alone when no Timestamp Header is configured
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignatureConfig {
+    pub enabled: bool,
+    pub secret: String,
+    pub header: String,
+    pub timestamp_header: String,
+    pub tolerance: u64,
+}
+
+/// Structure for the Authentication Configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    pub enabled: bool,
+    pub basic_auth: bool,
+    pub keys: Vec<ApiKeyConfig>,
+    #[serde(default)]
+    pub signature: SignatureConfig,
 }
 
 //==============================================================================
@@ -47,8 +395,47 @@ pub struct AppConfig {
     pub web_root: String,
     pub main_directory: String,
     pub config_file: String,
+    #[serde(default = "default_data_directory")]
+    pub data_directory: String,
     pub mail_worker: u16,
+    /// Seconds until a `send` Request is answered with 504 where 0 means no Deadline
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u64,
     pub smtp: SMTPConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub tls: TLSConfig,
+    #[serde(default)]
+    pub access: AccessConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub dedup: DedupConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub links: LinkConfig,
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+    #[serde(default)]
+    pub threading: ThreadingConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub logging: LogConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub mailbox: MailboxConfig,
+    #[serde(default)]
+    pub circuit: CircuitConfig,
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
 }
 
 //==============================================================================
@@ -77,6 +464,12 @@ impl SMTPConfig {
             password: String::new(),
             full_name: String::new(),
             email_address: String::new(),
+            helo_name: String::new(),
+            connect_timeout: default_connect_timeout(),
+            command_timeout: default_command_timeout(),
+            send_timeout: default_send_timeout(),
+            dkim: DKIMConfig::new(),
+            headers: Vec::new(),
         }
     }
 }
@@ -94,6 +487,12 @@ impl Clone for SMTPConfig {
             password: self.password.clone(),
             full_name: self.full_name.clone(),
             email_address: self.email_address.clone(),
+            helo_name: self.helo_name.clone(),
+            connect_timeout: self.connect_timeout,
+            command_timeout: self.command_timeout,
+            send_timeout: self.send_timeout,
+            dkim: self.dkim.clone(),
+            headers: self.headers.clone(),
         }
     }
 }
@@ -111,10 +510,601 @@ impl fmt::Debug for SMTPConfig {
             .field("password", &"******")
             .field("full_name", &self.full_name)
             .field("email_address", &self.email_address)
+            .field("helo_name", &self.helo_name)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("command_timeout", &self.command_timeout)
+            .field("send_timeout", &self.send_timeout)
+            .field("dkim", &self.dkim)
+            .field("headers", &self.headers)
             .finish()
     }
 }
 
+//==============================================================================
+// Structure DKIMConfig Implementation
+
+impl Default for DKIMConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        DKIMConfig::new()
+    }
+}
+
+impl DKIMConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> DKIMConfig {
+        DKIMConfig {
+            enabled: false,
+            selector: String::new(),
+            domain: String::new(),
+            private_key: String::new(),
+            headers: [
+                "from",
+                "to",
+                "subject",
+                "date",
+                "message-id",
+                "reply-to",
+                "in-reply-to",
+                "references",
+                "mime-version",
+                "content-type",
+            ]
+            .iter()
+            .map(|name| String::from(*name))
+            .collect(),
+        }
+    }
+}
+
+//==============================================================================
+// Structure TLSConfig Implementation
+
+impl Default for TLSConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        TLSConfig::new()
+    }
+}
+
+impl TLSConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> TLSConfig {
+        TLSConfig {
+            enabled: false,
+            certificate: String::new(),
+            key: String::new(),
+            client_ca: String::new(),
+            require_client_cert: false,
+        }
+    }
+}
+
+//==============================================================================
+// Structure AccessConfig Implementation
+
+impl Default for AccessConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        AccessConfig::new()
+    }
+}
+
+impl AccessConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> AccessConfig {
+        AccessConfig {
+            enabled: false,
+            allow: Vec::new(),
+            trusted_proxies: Vec::new(),
+            forwarded_header: default_forwarded_header(),
+        }
+    }
+}
+
+//==============================================================================
+// Structure RateLimitConfig Implementation
+
+impl BucketConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(capacity: u32, per_minute: u32) -> BucketConfig {
+        BucketConfig {
+            capacity,
+            per_minute,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        RateLimitConfig::new()
+    }
+}
+
+impl RateLimitConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: false,
+            mode: String::from("refuse"),
+            max_queue_wait: 30,
+            global: BucketConfig::new(60, 60),
+            per_recipient: BucketConfig::new(20, 10),
+            per_caller: BucketConfig::new(30, 30),
+        }
+    }
+}
+
+//==============================================================================
+// Structure DedupConfig Implementation
+
+impl Default for DedupConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        DedupConfig::new()
+    }
+}
+
+impl DedupConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> DedupConfig {
+        DedupConfig {
+            enabled: false,
+            window: 300,
+            action: String::from("drop"),
+        }
+    }
+}
+
+//==============================================================================
+// Structure DigestConfig Implementation
+
+impl Default for DigestConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        DigestConfig::new()
+    }
+}
+
+impl DigestConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> DigestConfig {
+        DigestConfig {
+            enabled: false,
+            interval: 30,
+            max_items: 100,
+        }
+    }
+}
+
+//==============================================================================
+// Structure RoutingConfig Implementation
+
+impl Default for RoutingConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        RoutingConfig::new()
+    }
+}
+
+impl RoutingConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> RoutingConfig {
+        RoutingConfig {
+            enabled: false,
+            root: RouteConfig {
+                name: String::from("default"),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+//==============================================================================
+// Structure LinkConfig Implementation
+
+impl Default for LinkConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        LinkConfig::new()
+    }
+}
+
+impl LinkConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> LinkConfig {
+        LinkConfig {
+            enabled: false,
+            base_url: String::from("http://localhost:3100"),
+            secret: String::new(),
+            expiry: 86400,
+            silence_duration: 3600,
+            ack_duration: 86400,
+            suppress_acknowledged: false,
+        }
+    }
+}
+
+impl fmt::Debug for LinkConfig {
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkConfig")
+            .field("enabled", &self.enabled)
+            .field("base_url", &self.base_url)
+            .field("secret", &"******")
+            .field("expiry", &self.expiry)
+            .field("silence_duration", &self.silence_duration)
+            .field("ack_duration", &self.ack_duration)
+            .field("suppress_acknowledged", &self.suppress_acknowledged)
+            .finish()
+    }
+}
+
+//==============================================================================
+// Structure EscalationConfig Implementation
+
+impl Default for EscalationConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        EscalationConfig::new()
+    }
+}
+
+impl EscalationConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> EscalationConfig {
+        EscalationConfig {
+            enabled: false,
+            check_interval: 30,
+            policies: Vec::new(),
+        }
+    }
+}
+
+//==============================================================================
+// Structure ThreadingConfig Implementation
+
+impl Default for ThreadingConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        ThreadingConfig::new()
+    }
+}
+
+impl ThreadingConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> ThreadingConfig {
+        ThreadingConfig {
+            enabled: false,
+            domain: String::new(),
+            max_threads: 10000,
+        }
+    }
+}
+
+//==============================================================================
+// Structure ValidationConfig Implementation
+
+impl Default for ValidationConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        ValidationConfig::new()
+    }
+}
+
+impl ValidationConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> ValidationConfig {
+        ValidationConfig {
+            max_subject_length: 255,
+            max_header_length: 998,
+        }
+    }
+}
+
+//==============================================================================
+// Structure HealthConfig Implementation
+
+impl Default for HealthConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        HealthConfig::new()
+    }
+}
+
+impl HealthConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> HealthConfig {
+        HealthConfig {
+            cache_ttl: 15,
+            timeout: 5,
+        }
+    }
+}
+
+//==============================================================================
+// Structure MailboxConfig Implementation
+
+impl Default for MailboxConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        MailboxConfig::new()
+    }
+}
+
+impl MailboxConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> MailboxConfig {
+        MailboxConfig {
+            capacity: 1000,
+            overflow: String::from("refuse"),
+            retry_after: 5,
+        }
+    }
+}
+
+//==============================================================================
+// Structure CircuitConfig Implementation
+
+impl Default for CircuitConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        CircuitConfig::new()
+    }
+}
+
+impl CircuitConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> CircuitConfig {
+        CircuitConfig {
+            enabled: false,
+            failure_threshold: 5,
+            open_timeout: 30,
+            mode: String::from("refuse"),
+        }
+    }
+}
+
+//==============================================================================
+// Structure OutboxConfig Implementation
+
+impl Default for OutboxConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        OutboxConfig::new()
+    }
+}
+
+impl OutboxConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> OutboxConfig {
+        OutboxConfig {
+            enabled: false,
+            interval: default_outbox_interval(),
+        }
+    }
+}
+
+//==============================================================================
+// Structure ShutdownConfig Implementation
+
+impl Default for ShutdownConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        ShutdownConfig::new()
+    }
+}
+
+impl ShutdownConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> ShutdownConfig {
+        ShutdownConfig { drain_timeout: 30 }
+    }
+}
+
+//==============================================================================
+// Structure LogConfig Implementation
+
+impl Default for LogConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        LogConfig::new()
+    }
+}
+
+impl LogConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> LogConfig {
+        LogConfig {
+            level: String::from("info"),
+            format: String::from("json"),
+            log_bodies: false,
+        }
+    }
+}
+
+//==============================================================================
+// Structure SignatureConfig Implementation
+
+impl Default for SignatureConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        SignatureConfig::new()
+    }
+}
+
+impl SignatureConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> SignatureConfig {
+        SignatureConfig {
+            enabled: false,
+            secret: String::new(),
+            header: String::from("X-Grafana-Alerting-Signature"),
+            timestamp_header: String::from("X-Grafana-Alerting-Signature-Timestamp"),
+            tolerance: 300,
+        }
+    }
+}
+
+impl fmt::Debug for SignatureConfig {
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignatureConfig")
+            .field("enabled", &self.enabled)
+            .field("secret", &"******")
+            .field("header", &self.header)
+            .field("timestamp_header", &self.timestamp_header)
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+//==============================================================================
+// Structure AuthConfig Implementation
+
+impl Default for AuthConfig {
+    /*----------------------------------------------------------------------------
+     * Default Constructor
+     */
+
+    fn default() -> Self {
+        AuthConfig::new()
+    }
+}
+
+impl AuthConfig {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> AuthConfig {
+        AuthConfig {
+            enabled: false,
+            basic_auth: false,
+            keys: Vec::new(),
+            signature: SignatureConfig::new(),
+        }
+    }
+}
+
 //==============================================================================
 // Structure AppConfig Implementation
 
@@ -140,8 +1130,27 @@ impl AppConfig {
             web_root: String::from("/"),
             main_directory: String::new(),
             config_file: String::new(),
+            data_directory: default_data_directory(),
             mail_worker: 2,
+            request_timeout: default_request_timeout(),
             smtp: SMTPConfig::new(),
+            auth: AuthConfig::new(),
+            tls: TLSConfig::new(),
+            access: AccessConfig::new(),
+            rate_limit: RateLimitConfig::new(),
+            dedup: DedupConfig::new(),
+            digest: DigestConfig::new(),
+            routing: RoutingConfig::new(),
+            links: LinkConfig::new(),
+            escalation: EscalationConfig::new(),
+            threading: ThreadingConfig::new(),
+            validation: ValidationConfig::new(),
+            logging: LogConfig::new(),
+            health: HealthConfig::new(),
+            mailbox: MailboxConfig::new(),
+            circuit: CircuitConfig::new(),
+            outbox: OutboxConfig::new(),
+            shutdown: ShutdownConfig::new(),
         }
     }
 
@@ -152,7 +1161,9 @@ project: 'Actix Alerting Email'
 web_root: '/'
 main_directory: ''
 config file: ''
+data_directory: 'data'
 mail_worker: 2
+request_timeout: 120
 smtp:
   host: ''
   port: ''
@@ -160,12 +1171,109 @@ smtp:
   password: ''
   full_name: ''
   email_address: ''
+  helo_name: ''
+  connect_timeout: 10
+  command_timeout: 15
+  send_timeout: 60
+  dkim:
+    enabled: false
+    selector: ''
+    domain: ''
+    private_key: ''
+    headers: ['from', 'to', 'subject', 'date', 'message-id', 'reply-to', 'in-reply-to', 'references', 'mime-version', 'content-type']
+  headers: []
+auth:
+  enabled: false
+  basic_auth: false
+  keys: []
+  signature:
+    enabled: false
+    secret: ''
+    header: 'X-Grafana-Alerting-Signature'
+    timestamp_header: 'X-Grafana-Alerting-Signature-Timestamp'
+    tolerance: 300
+tls:
+  enabled: false
+  certificate: ''
+  key: ''
+  client_ca: ''
+  require_client_cert: false
+access:
+  enabled: false
+  allow: []
+  trusted_proxies: []
+  forwarded_header: 'X-Forwarded-For'
+rate_limit:
+  enabled: false
+  mode: 'refuse'
+  max_queue_wait: 30
+  global:
+    capacity: 60
+    per_minute: 60
+  per_recipient:
+    capacity: 20
+    per_minute: 10
+  per_caller:
+    capacity: 30
+    per_minute: 30
+dedup:
+  enabled: false
+  window: 300
+  action: 'drop'
+digest:
+  enabled: false
+  interval: 30
+  max_items: 100
+routing:
+  enabled: false
+  root:
+    name: 'default'
+links:
+  enabled: false
+  base_url: 'http://localhost:3100'
+  secret: ''
+  expiry: 86400
+  silence_duration: 3600
+  ack_duration: 86400
+  suppress_acknowledged: false
+escalation:
+  enabled: false
+  check_interval: 30
+  policies: []
+threading:
+  enabled: false
+  domain: ''
+  max_threads: 10000
+validation:
+  max_subject_length: 255
+  max_header_length: 998
+logging:
+  level: 'info'
+  format: 'json'
+  log_bodies: false
+health:
+  cache_ttl: 15
+  timeout: 5
+mailbox:
+  capacity: 1000
+  overflow: 'refuse'
+  retry_after: 5
+circuit:
+  enabled: false
+  failure_threshold: 5
+  open_timeout: 30
+  mode: 'refuse'
+outbox:
+  enabled: false
+  interval: 30
+shutdown:
+  drain_timeout: 30
 ";
         // Deserialize it back to a Rust type.
         let config: AppConfig = match serde_yaml::from_str(&config_yaml) {
             Ok(cfg) => cfg,
             Err(e) => {
-                eprintln!("Default Config could not be parsed: {:?}", e);
+                error!("Default Config could not be parsed: {:?}", e);
                 AppConfig::new()
             }
         };
@@ -181,13 +1289,13 @@ smtp:
                 config = match try_config_from_path(&file) {
                     Ok(cfg) => Some(cfg),
                     Err(e) => {
-                        eprintln!("Config File {:?}: File could not be read: {:?}", file, e);
+                        error!("Config File {:?}: File could not be read: {:?}", file, e);
                         None
                     }
                 };
             }
             Err(e) => {
-                eprintln!(
+                warn!(
                     "Config File '{}': File could not be found: {:?}",
                     CONFIG_FILE, e
                 );
@@ -195,7 +1303,7 @@ smtp:
         };
 
         if config.is_none() {
-            eprintln!("Falling back to default configuration ...");
+            warn!("Falling back to default configuration ...");
             config = Some(AppConfig::from_yaml());
         }
 
@@ -204,6 +1312,39 @@ smtp:
             None => AppConfig::new(),
         }
     }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Checks that a `send` Request cannot time out while its Email is still sent
+    /// The `request_timeout` must exceed the `smtp.send_timeout` and the `max_queue_wait`
+    /// of the Rate Limit in `queue` Mode
+    pub fn check_timeouts(&self) -> Result<(), Error> {
+        if self.request_timeout == 0 {
+            return Ok(());
+        }
+
+        let queue_wait = if self.rate_limit.enabled && self.rate_limit.mode == "queue" {
+            self.rate_limit.max_queue_wait
+        } else {
+            0
+        };
+
+        if self.smtp.send_timeout == 0
+            || self.request_timeout <= self.smtp.send_timeout.saturating_add(queue_wait)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Config: 'request_timeout' {}s must exceed 'smtp.send_timeout' {}s and 'rate_limit.max_queue_wait' {}s",
+                    self.request_timeout, self.smtp.send_timeout, queue_wait
+                ),
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 impl Clone for AppConfig {
@@ -218,8 +1359,27 @@ impl Clone for AppConfig {
             web_root: self.web_root.clone(),
             main_directory: self.main_directory.clone(),
             config_file: self.config_file.clone(),
+            data_directory: self.data_directory.clone(),
             mail_worker: self.mail_worker,
+            request_timeout: self.request_timeout,
             smtp: self.smtp.clone(),
+            auth: self.auth.clone(),
+            tls: self.tls.clone(),
+            access: self.access.clone(),
+            rate_limit: self.rate_limit.clone(),
+            dedup: self.dedup.clone(),
+            digest: self.digest.clone(),
+            routing: self.routing.clone(),
+            links: self.links.clone(),
+            escalation: self.escalation.clone(),
+            threading: self.threading.clone(),
+            validation: self.validation.clone(),
+            logging: self.logging.clone(),
+            health: self.health.clone(),
+            mailbox: self.mailbox.clone(),
+            circuit: self.circuit.clone(),
+            outbox: self.outbox.clone(),
+            shutdown: self.shutdown.clone(),
         }
     }
 }
@@ -227,6 +1387,34 @@ impl Clone for AppConfig {
 //==============================================================================
 // Auxiliary Functions
 
+fn default_data_directory() -> String {
+    String::from("data")
+}
+
+fn default_forwarded_header() -> String {
+    String::from("X-Forwarded-For")
+}
+
+fn default_outbox_interval() -> u64 {
+    30
+}
+
+fn default_request_timeout() -> u64 {
+    120
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_command_timeout() -> u64 {
+    15
+}
+
+fn default_send_timeout() -> u64 {
+    60
+}
+
 fn try_find_file(file: &Path) -> Result<PathBuf, Error> {
     let work_dir = std::env::current_dir().map_err(|e| {
         Error::new(
@@ -237,14 +1425,14 @@ fn try_find_file(file: &Path) -> Result<PathBuf, Error> {
             ),
         )
     })?;
-    println!("Working Directory: '{}'", work_dir.display());
+    debug!("Working Directory: '{}'", work_dir.display());
 
     let mut search_dir: Option<&Path> = Some(Path::new(work_dir.as_path()));
     let mut find_file: Option<PathBuf> = None;
 
     while search_dir.is_some() && find_file.is_none() {
         if let Some(d) = search_dir {
-            println!("Search Directory: '{}'", d.display());
+            debug!("Search Directory: '{}'", d.display());
 
             let mut search_file = PathBuf::from(d);
 
@@ -0,0 +1,170 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Request Correlation
+
+* This Module defines the Middleware that assigns each Request its Request ID
+* so that the Access Log, the Application Log and the SMTP Transaction can be linked
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "ring" must be installed
+*/
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use futures_util::future::{ok, FutureExt, LocalBoxFuture, Ready};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::logging;
+
+/// Header that carries the Request ID in the Request, the Response and the Email
+pub const REQUEST_ID_HEADER: &str = "X-Request-ID";
+
+/// Longest Request ID that is accepted from the Caller
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+//==============================================================================
+// Structure RequestId Declaration
+
+/// Structure for the Request ID of the current Request
+/// It is stored in the Request Extensions by the `Correlation` Middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+//==============================================================================
+// Structure Correlation Declaration
+
+/// Middleware to accept the `X-Request-ID` of the Caller or to generate a new one
+/// The Request ID is added to each Log Line and returned in the Response
+pub struct Correlation;
+
+pub struct CorrelationMiddleware<S> {
+    service: S,
+}
+
+/// Future that sets the Request ID for the Log Lines each Time it is polled
+/// because the Requests of one HTTP Worker take Turns on the same Thread
+pub struct RequestScope<F> {
+    future: F,
+    request_id: String,
+}
+
+//==============================================================================
+// Structure Correlation Implementation
+
+impl<S, B> Transform<S> for Correlation
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CorrelationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CorrelationMiddleware { service })
+    }
+}
+
+impl<S, B> Service for CorrelationMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = RequestScope<LocalBoxFuture<'static, Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| is_valid(value))
+            .map(str::to_owned)
+            .unwrap_or_else(new_request_id);
+
+        req.extensions_mut().insert(RequestId(request_id.clone()));
+
+        let header_value = HeaderValue::from_str(&request_id).ok();
+        let future = logging::with_request_id(Some(&request_id), || self.service.call(req));
+
+        RequestScope {
+            future: async move {
+                let mut res = future.await?;
+
+                if let Some(value) = header_value {
+                    res.headers_mut()
+                        .insert(HeaderName::from_static("x-request-id"), value);
+                }
+
+                Ok(res)
+            }
+            .boxed_local(),
+            request_id,
+        }
+    }
+}
+
+//==============================================================================
+// Structure RequestScope Implementation
+
+impl<F> Future for RequestScope<F>
+where
+    F: Future + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RequestScope { future, request_id } = &mut *self;
+
+        logging::with_request_id(Some(request_id.as_str()), || Pin::new(future).poll(cx))
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Creates a new random Request ID of 32 Hex Digits
+pub fn new_request_id() -> String {
+    let mut bytes = [0u8; 16];
+
+    match SystemRandom::new().fill(&mut bytes) {
+        Ok(()) => hex::encode(bytes),
+        // Fall back to the Job Counter if the System has no Randomness
+        Err(_) => crate::email::new_job_id(),
+    }
+}
+
+/// Accepts only short Request IDs which are safe in Log Lines and Email Headers
+pub fn is_valid(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
@@ -0,0 +1,200 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Deduplication
+
+* This Module defines the Cache that detects repeated Alerts within a Time Window
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use crate::config::DedupConfig;
+use crate::email::{self, EmailData, EmailLink, EmailResponse};
+
+//==============================================================================
+// Structure DedupCache Declaration
+
+/// Structure for the shared Deduplication Cache of all HTTP Workers
+#[derive(Clone)]
+pub struct DedupCache {
+    config: DedupConfig,
+    entries: Arc<Mutex<HashMap<String, DedupEntry>>>,
+}
+
+/// Structure for the first Email of an Alert within the Time Window
+#[derive(Debug, Clone)]
+struct DedupEntry {
+    job: String,
+    first_seen: Instant,
+    duplicates: u32,
+    // The original Email is kept in `merge` Mode to report the Duplicates when the Window closes
+    email: Option<EmailData>,
+}
+
+//==============================================================================
+// Structure DedupCache Implementation
+
+impl DedupCache {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &DedupConfig) -> Self {
+        Self {
+            config: config.clone(),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Registers the Email with the Fingerprint of the Alert as it was received
+    /// A Duplicate within the Time Window gets a `deduplicated` Response that points
+    /// to the original Job. In `merge` Mode the Count of Duplicates of an expired Window
+    /// which was not reported yet is carried over to the next Email of the same Alert.
+    pub fn check(&self, email: &mut EmailData, fingerprint: &str) -> Option<EmailResponse> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let window = Duration::from_secs(self.config.window);
+        let merge = self.config.action == "merge";
+        let now = Instant::now();
+        let mut entries = match self.entries.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+        let mut merged = 0;
+
+        if let Some(entry) = entries.get_mut(fingerprint) {
+            if now.duration_since(entry.first_seen) < window {
+                entry.duplicates += 1;
+
+                return Some(EmailResponse {
+                    status: String::from("deduplicated"),
+                    report: format!(
+                        "Email is a Duplicate of Job '{}' ({} Duplicates within {}s)",
+                        entry.job, entry.duplicates, self.config.window
+                    ),
+                    job: entry.job.clone(),
+                });
+            }
+
+            merged = entry.duplicates;
+        }
+
+        // Expired Entries are removed before the new Window starts
+        // unless their Duplicates still need to be reported by `expire()`
+        entries.retain(|_, entry| {
+            now.duration_since(entry.first_seen) < window
+                || (entry.duplicates > 0 && entry.email.is_some())
+        });
+
+        if merge {
+            email.duplicates = merged;
+        }
+
+        entries.insert(
+            fingerprint.to_owned(),
+            DedupEntry {
+                job: email.job.clone(),
+                first_seen: now,
+                duplicates: 0,
+                email: if merge { Some(email.clone()) } else { None },
+            },
+        );
+
+        None
+    }
+
+    /// Removes the expired Windows
+    /// In `merge` Mode it returns a Summary Email for each Alert that had Duplicates
+    /// within its Window, so that they are reported even if the Alert does not fire again
+    pub fn expire(&self) -> Vec<EmailData> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let window = Duration::from_secs(self.config.window);
+        let now = Instant::now();
+        let mut entries = match self.entries.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+        let mut summaries = Vec::new();
+
+        entries.retain(|_, entry| {
+            if now.duration_since(entry.first_seen) < window {
+                return true;
+            }
+
+            if entry.duplicates > 0 {
+                if let Some(mut email) = entry.email.take() {
+                    email.job = email::new_job_id();
+                    email.duplicates = entry.duplicates;
+                    email.message = format!(
+                        "{}\n\n{} Duplicates of this Alert were suppressed within {}s.\n",
+                        email.message,
+                        entry.duplicates,
+                        window.as_secs()
+                    );
+
+                    summaries.push(email);
+                }
+            }
+
+            false
+        });
+
+        summaries
+    }
+
+    /// Sends the Summaries of the closed Windows in `merge` Mode
+    /// The Windows are checked every tenth of the `window` but at least every 60s
+    pub async fn watch(self, link: EmailLink) {
+        let interval = Duration::from_secs((self.config.window / 10).max(1).min(60));
+
+        loop {
+            actix_rt::time::delay_for(interval).await;
+
+            for summary in self.expire() {
+                let job = summary.job.clone();
+
+                match email::send_mail(&link, summary).await {
+                    Ok(rs) => info!("dedup summary '{}': '{:?}'", job, rs),
+                    Err(e) => warn!("dedup summary '{}': failed: '{:?}'", job, e),
+                }
+            }
+        }
+    }
+
+    /// Removes the Alert of a failed Email so that a Retry will be sent
+    pub fn forget(&self, fingerprint: &str, job: &str) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut entries = match self.entries.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+
+        if entries
+            .get(fingerprint)
+            .map(|entry| entry.job == job)
+            .unwrap_or(false)
+        {
+            entries.remove(fingerprint);
+        }
+    }
+}
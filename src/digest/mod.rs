@@ -0,0 +1,244 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Digest Actor
+
+* This Module defines the Actor that collects Alerts per Recipient
+* and sends them periodically as one Summary Email
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use actix::prelude::*;
+use log::{error, info};
+
+use crate::config::DigestConfig;
+use crate::email::{self, EmailData, EmailLink, EmailResponse};
+use crate::threading::ThreadStore;
+
+//==============================================================================
+// Structure DigestMessage Declaration
+
+/// Message to add an Email to the Digest of its Recipients
+#[derive(Debug, Message)]
+#[rtype(result = "EmailResponse")]
+pub struct DigestAdd(pub EmailData);
+
+/// Message to send all collected Digests immediately
+/// It is also sent at the Shutdown so that the Digests are drained with the other Emails
+/// The Result is the Count of Digest Emails that were dispatched
+#[derive(Debug, Message)]
+#[rtype(result = "usize")]
+pub struct DigestFlush;
+
+//==============================================================================
+// Structure DigestActor Declaration
+
+/// Structure for the Digest Collection
+pub struct DigestActor {
+    config: DigestConfig,
+    /// The configured Address which the unrouted Emails are delivered to
+    default_address: String,
+    link: EmailLink,
+    threads: Option<ThreadStore>,
+    digests: BTreeMap<String, Vec<EmailData>>,
+}
+
+//==============================================================================
+// Structure DigestActor Implementation
+
+impl DigestActor {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &DigestConfig, default_address: &str, link: EmailLink) -> Self {
+        Self {
+            config: config.clone(),
+            default_address: default_address.to_owned(),
+            link,
+            threads: None,
+            digests: BTreeMap::new(),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Sets the Thread Store which threads the Digests of each Recipient
+    pub fn set_threads(&mut self, threads: &ThreadStore) {
+        self.threads = Some(threads.clone());
+    }
+
+    /// Adds the Email to the Digest of the Addresses it is delivered to
+    /// so that unrouted Emails of all Callers share the Digest of the configured Address
+    /// The Digest is sent when `max_items` Alerts are collected
+    pub fn add(&mut self, mail: EmailData) -> EmailResponse {
+        let recipient = mail
+            .delivery_addresses(&self.default_address)
+            .join(", ")
+            .to_lowercase();
+        let job = mail.job.clone();
+        let entries = self.digests.entry(recipient.clone()).or_default();
+
+        entries.push(mail);
+
+        let count = entries.len();
+
+        if self.config.max_items > 0 && count >= self.config.max_items {
+            info!(
+                "Digest '{}': {} Alerts collected; flushing ...",
+                recipient, count
+            );
+
+            self.flush();
+        }
+
+        EmailResponse {
+            status: String::from("queued"),
+            report: format!(
+                "Email was added to the Digest for '{}' ({} Alerts)",
+                recipient, count
+            ),
+            job,
+        }
+    }
+
+    /// Builds the Digest Emails of all Recipients and empties the Collection
+    /// The Digests of a Recipient are linked into one Thread
+    pub fn take(&mut self) -> Vec<EmailData> {
+        let digests = std::mem::take(&mut self.digests);
+        let mut mails = Vec::with_capacity(digests.len());
+
+        for (recipient, collected) in digests {
+            let mut digest = build_digest(&recipient, collected);
+
+            digest.thread_id = Some(format!("digest:{}", recipient));
+
+            if let Some(threads) = &self.threads {
+                threads.assign(&mut digest);
+            }
+
+            mails.push(digest);
+        }
+
+        mails
+    }
+
+    /// Sends the collected Alerts of each Recipient as one Email
+    pub fn flush(&mut self) -> usize {
+        let digests = self.take();
+        let count = digests.len();
+
+        for digest in digests {
+            let job = digest.job.clone();
+
+            info!("Digest '{}': sending Job '{}' ...", digest.to, job);
+
+            // The Email is counted as in Flight before the Task runs
+            let delivery = self.link.send_email(digest);
+
+            actix_rt::spawn(async move {
+                match delivery.await {
+                    Ok(rs) => info!("Digest Job '{}': {:?}", job, rs),
+                    Err(e) => error!("Digest Job '{}': failed: {:?}", job, e),
+                }
+            });
+        }
+
+        count
+    }
+}
+
+impl Actor for DigestActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        info!(
+            "Digest Actor is alive; flushing every {} minutes",
+            self.config.interval
+        );
+
+        ctx.run_interval(
+            Duration::from_secs(self.config.interval.max(1) * 60),
+            |act, _ctx| {
+                act.flush();
+            },
+        );
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        info!("Digest Actor is stopped");
+    }
+}
+
+/// Define handler for `DigestAdd` Message
+impl Handler<DigestAdd> for DigestActor {
+    type Result = MessageResult<DigestAdd>;
+
+    fn handle(&mut self, msg: DigestAdd, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.add(msg.0))
+    }
+}
+
+/// Define handler for `DigestFlush` Message
+impl Handler<DigestFlush> for DigestActor {
+    type Result = MessageResult<DigestFlush>;
+
+    fn handle(&mut self, _msg: DigestFlush, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.flush())
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Combines the Alerts into one Email with a Table of Contents
+fn build_digest(recipient: &str, mails: Vec<EmailData>) -> EmailData {
+    let mut contents = String::new();
+    let mut sections = String::new();
+    let title = format!("Digest of {} Alerts", mails.len());
+
+    for (index, mail) in mails.iter().enumerate() {
+        contents.push_str(&format!("{:>3}. {}\n", index + 1, mail.subject));
+        sections.push_str(&format!(
+            "\n{}\n{:>3}. {}\nFrom: {} | Job: {}\n\n{}\n",
+            "-".repeat(60),
+            index + 1,
+            mail.subject,
+            mail.from,
+            mail.job,
+            mail.message.trim_end()
+        ));
+    }
+
+    EmailData {
+        subject: format!("[Digest] {} Alerts", mails.len()),
+        from: mails
+            .first()
+            .map(|mail| mail.from.clone())
+            .unwrap_or_default(),
+        to: recipient.to_owned(),
+        // Routed Emails keep the Recipients and the Sender of their Route
+        deliver_to: mails
+            .first()
+            .map(|mail| mail.deliver_to.clone())
+            .unwrap_or_default(),
+        sender: mails.first().and_then(|mail| mail.sender.clone()),
+        message: format!(
+            "{}\n{}\n\n{}{}",
+            title,
+            "=".repeat(title.len()),
+            contents,
+            sections
+        ),
+        job: email::new_job_id(),
+        ..Default::default()
+    }
+}
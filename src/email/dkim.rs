@@ -0,0 +1,344 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage DKIM Signing
+
+* This Module defines the DKIM Signing of the built Messages (RFC 6376 and RFC 8463)
+* with the "relaxed/relaxed" Canonicalization
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "ring" must be installed
+* - The Rust Crate "rustls" must be installed
+*/
+
+use std::io::{Error, ErrorKind};
+
+use lettre::SendableEmail;
+use ring::rand::SystemRandom;
+use ring::signature::{self, Ed25519KeyPair, KeyPair, RsaKeyPair, UnparsedPublicKey};
+use sha2::{Digest, Sha256};
+
+use crate::config::DKIMConfig;
+use crate::store;
+use crate::tls;
+
+const DKIM_HEADER: &str = "DKIM-Signature";
+
+//==============================================================================
+// Structure DkimSigner Declaration
+
+/// Structure for the Private Key of the Signer
+pub enum DkimKey {
+    Rsa(RsaKeyPair),
+    Ed25519(Ed25519KeyPair),
+}
+
+/// Structure for the DKIM Signer of one Email Sending Thread
+pub struct DkimSigner {
+    config: DKIMConfig,
+    key: DkimKey,
+}
+
+//==============================================================================
+// Structure DkimSigner Implementation
+
+impl DkimSigner {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Loads the RSA or Ed25519 Private Key from the configured PEM File
+    pub fn from_config(config: &DKIMConfig) -> Result<Self, Error> {
+        let der = tls::load_private_key(&config.private_key)?.0;
+        let key = if let Ok(key) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der) {
+            DkimKey::Ed25519(key)
+        } else if let Ok(key) = RsaKeyPair::from_pkcs8(&der) {
+            DkimKey::Rsa(key)
+        } else {
+            let key = RsaKeyPair::from_der(&der).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "DKIM Key File '{}': Key is neither RSA nor Ed25519: '{}'",
+                        config.private_key, e
+                    ),
+                )
+            })?;
+
+            DkimKey::Rsa(key)
+        };
+
+        Ok(Self::from_key(config, key))
+    }
+
+    pub fn from_key(config: &DKIMConfig, key: DkimKey) -> Self {
+        Self {
+            config: config.clone(),
+            key,
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Prepends the `DKIM-Signature` Header to the built Email
+    pub fn sign_email(&self, email: SendableEmail) -> Result<SendableEmail, Error> {
+        let envelope = email.envelope().clone();
+        let message_id = email.message_id().to_owned();
+        let message = email.message_to_string()?;
+        let signature = self.sign(&message)?;
+
+        Ok(SendableEmail::new(
+            envelope,
+            message_id,
+            format!("{}\r\n{}", signature, message).into_bytes(),
+        ))
+    }
+
+    /// Computes the `DKIM-Signature` Header Field for the Message
+    pub fn sign(&self, message: &str) -> Result<String, Error> {
+        let (headers, body) = split_message(message);
+        let fields = parse_headers(headers);
+        let signed = select_headers(&fields, self.config.headers.iter().map(String::as_str));
+        let value = format!(
+            "v=1; a={}; c=relaxed/relaxed; d={}; s={}; t={}; h={}; bh={}; b=",
+            self.algorithm(),
+            self.config.domain,
+            self.config.selector,
+            store::unix_time(),
+            signed
+                .iter()
+                .map(|(name, _)| name.to_lowercase())
+                .collect::<Vec<String>>()
+                .join(":"),
+            base64::encode(Sha256::digest(relaxed_body(body).as_bytes()))
+        );
+        let mut data = String::new();
+
+        for (name, value) in signed {
+            data.push_str(&relaxed_header(name, value));
+            data.push_str("\r\n");
+        }
+
+        data.push_str(&relaxed_header(DKIM_HEADER, &value));
+
+        let signature = match &self.key {
+            DkimKey::Rsa(key) => {
+                let mut signature = vec![0; key.public_modulus_len()];
+
+                key.sign(
+                    &signature::RSA_PKCS1_SHA256,
+                    &SystemRandom::new(),
+                    data.as_bytes(),
+                    &mut signature,
+                )
+                .map_err(|_| Error::new(ErrorKind::Other, "DKIM: RSA Signing failed"))?;
+
+                signature
+            }
+            DkimKey::Ed25519(key) => key.sign(&Sha256::digest(data.as_bytes())).as_ref().to_vec(),
+        };
+
+        Ok(format!(
+            "{}: {}{}",
+            DKIM_HEADER,
+            value,
+            base64::encode(signature)
+        ))
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn algorithm(&self) -> &'static str {
+        match self.key {
+            DkimKey::Rsa(_) => "rsa-sha256",
+            DkimKey::Ed25519(_) => "ed25519-sha256",
+        }
+    }
+
+    /// Returns the Public Key as published in the DNS Record
+    /// (`RSAPublicKey` DER for RSA and the raw Key for Ed25519)
+    pub fn public_key(&self) -> Vec<u8> {
+        match &self.key {
+            DkimKey::Rsa(key) => key.public_key().as_ref().to_vec(),
+            DkimKey::Ed25519(key) => key.public_key().as_ref().to_vec(),
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Verifies the first `DKIM-Signature` of the Message with the Public Key
+pub fn verify(message: &str, public_key: &[u8]) -> Result<(), String> {
+    let (headers, body) = split_message(message);
+    let fields = parse_headers(headers);
+    let (_, dkim) = fields
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(DKIM_HEADER))
+        .ok_or_else(|| String::from("DKIM-Signature is missing"))?;
+    let tag = |name: &str| -> Option<String> {
+        dkim.split(';').find_map(|tag| {
+            let mut parts = tag.splitn(2, '=');
+
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key.trim() == name => {
+                    Some(value.split_whitespace().collect::<String>())
+                }
+                _ => None,
+            }
+        })
+    };
+
+    if tag("c").as_deref() != Some("relaxed/relaxed") {
+        return Err(String::from("Canonicalization is not supported"));
+    }
+
+    let body_hash = base64::encode(Sha256::digest(relaxed_body(body).as_bytes()));
+
+    if tag("bh").as_deref() != Some(body_hash.as_str()) {
+        return Err(String::from("Body Hash does not match"));
+    }
+
+    let signature = tag("b")
+        .and_then(|b| base64::decode(b).ok())
+        .ok_or_else(|| String::from("Signature is malformed"))?;
+    let mut data = String::new();
+
+    for (name, value) in select_headers(&fields, tag("h").unwrap_or_default().split(':')) {
+        data.push_str(&relaxed_header(name, value));
+        data.push_str("\r\n");
+    }
+
+    // The Signature is computed with an empty `b` Tag
+    let unsigned: Vec<String> = dkim
+        .split(';')
+        .map(|tag| match tag.find('=') {
+            Some(index) if tag[..index].trim() == "b" => tag[..=index].to_owned(),
+            _ => tag.to_owned(),
+        })
+        .collect();
+
+    data.push_str(&relaxed_header(DKIM_HEADER, &unsigned.join(";")));
+
+    match tag("a").as_deref() {
+        Some("rsa-sha256") => {
+            UnparsedPublicKey::new(&signature::RSA_PKCS1_2048_8192_SHA256, public_key)
+                .verify(data.as_bytes(), &signature)
+        }
+        Some("ed25519-sha256") => UnparsedPublicKey::new(&signature::ED25519, public_key)
+            .verify(&Sha256::digest(data.as_bytes()), &signature),
+        _ => return Err(String::from("Algorithm is not supported")),
+    }
+    .map_err(|_| String::from("Signature does not match"))
+}
+
+/// Splits the Message into the Header Block and the Body
+fn split_message(message: &str) -> (&str, &str) {
+    match message.find("\r\n\r\n") {
+        Some(index) => (&message[..index], &message[index + 4..]),
+        None => (message, ""),
+    }
+}
+
+/// Parses the Header Fields with their folded Values
+fn parse_headers(headers: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+
+    for line in headers.split("\r\n") {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push_str("\r\n");
+                value.push_str(line);
+            }
+        } else {
+            let mut parts = line.splitn(2, ':');
+
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                fields.push((name.to_owned(), value.to_owned()));
+            }
+        }
+    }
+
+    fields
+}
+
+/// Selects the Header Fields for the Names of the `h` Tag from the Bottom up
+/// A repeated Name selects the next higher Instance of the Field
+/// and Names without a further Instance are skipped (RFC 6376 Section 5.4.2)
+fn select_headers<'a, 'n>(
+    fields: &'a [(String, String)],
+    names: impl Iterator<Item = &'n str>,
+) -> Vec<&'a (String, String)> {
+    let mut selected: Vec<String> = Vec::new();
+
+    names
+        .filter_map(|name| {
+            let name = name.trim().to_lowercase();
+            let instance = selected.iter().filter(|n| **n == name).count();
+            let field = fields
+                .iter()
+                .rev()
+                .filter(|(field, _)| field.trim().eq_ignore_ascii_case(&name))
+                .nth(instance);
+
+            selected.push(name);
+
+            field
+        })
+        .collect()
+}
+
+/// Canonicalizes the Header Field with the "relaxed" Algorithm
+fn relaxed_header(name: &str, value: &str) -> String {
+    format!(
+        "{}:{}",
+        name.trim().to_lowercase(),
+        value
+            .replace("\r\n", "")
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    )
+}
+
+/// Canonicalizes the Body with the "relaxed" Algorithm
+fn relaxed_body(body: &str) -> String {
+    let mut lines: Vec<String> = body
+        .split("\r\n")
+        .map(|line| {
+            let mut canonical = String::with_capacity(line.len());
+            let mut space = false;
+
+            for c in line.chars() {
+                if c == ' ' || c == '\t' {
+                    space = true;
+                } else {
+                    if space {
+                        canonical.push(' ');
+                        space = false;
+                    }
+
+                    canonical.push(c);
+                }
+            }
+
+            canonical
+        })
+        .collect();
+
+    while lines.last().map(String::is_empty).unwrap_or(false) {
+        lines.pop();
+    }
+
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\r\n") + "\r\n"
+    }
+}
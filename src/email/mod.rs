@@ -1,6 +1,6 @@
 /*
 * @author Bodo (Hugo) Barwich
-* @version 2024-02-04
+* @version 2026-10-18
 * @package Grafana Alerting
 * @subpackage Email Sending Actor
 
@@ -10,34 +10,105 @@
 * Requirements:
 */
 
+pub mod dkim;
+
 use actix::prelude::*;
 use actix::Addr;
 use serde::{Deserialize, Serialize};
 
-use lettre::smtp::client::net::ClientTlsParameters;
+use lettre::smtp::client::net::{ClientTlsParameters, NetworkStream};
+use lettre::smtp::client::InnerClient;
+use lettre::smtp::commands::{EhloCommand, NoopCommand, QuitCommand};
+use lettre::smtp::error::Error as SmtpError;
 use lettre::smtp::{
     authentication::Credentials, authentication::Mechanism, extension::ClientId, SmtpClient,
     SmtpTransport,
 };
-use lettre::{ClientSecurity, Transport};
+use lettre::{ClientSecurity, SendableEmail, Transport};
 use lettre_email::{EmailBuilder, Header};
+use log::{debug, error, info};
 use native_tls::TlsConnector;
 
 use core::time::Duration;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
 
-use super::config::SMTPConfig;
+use super::circuit::CircuitBreaker;
+use super::config::{MailboxConfig, SMTPConfig, SenderConfig};
+use super::correlation::REQUEST_ID_HEADER;
+use super::logging;
+use super::metrics::Metrics;
+use super::monitor::{WorkerEvent, WorkerMonitor, WorkerReport};
+use super::outbox::Outbox;
+use dkim::DkimSigner;
 
 //==============================================================================
 // Structure EmailData Declaration
 
 /// Structure for Incoming Data
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 //#[rtype(result = "Result<EmailResponse, EmailError>")]
 pub struct EmailData {
     pub subject: String,
     pub from: String,
     pub to: String,
     pub message: String,
+    /// Name of the API Key the Request was authenticated with
+    #[serde(skip)]
+    pub caller: Option<String>,
+    /// Client Address the Request was received from
+    #[serde(skip)]
+    pub client_address: Option<String>,
+    /// Caller supplied Alert Identifier used for the Deduplication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// Address the Replies go to instead of the Sender Identity
+    /// Without it the `from` Address is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+    /// Extra Headers like `X-Priority`, `Importance` or `X-Alert-*`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Caller supplied Thread Identifier that groups the Emails of one Alert
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    /// Labels of the Alert like `severity`, `team` or `service`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Collect the Email into the periodic Digest instead of sending it
+    #[serde(default)]
+    pub digest: bool,
+    /// Job Identifier assigned when the Request is accepted
+    #[serde(skip)]
+    pub job: String,
+    /// Request ID of the HTTP Request that links the Log Lines to the SMTP Transaction
+    #[serde(skip)]
+    pub request_id: Option<String>,
+    /// Count of Duplicates that were merged into this Email
+    #[serde(skip)]
+    pub duplicates: u32,
+    /// Name of the Route that was selected by the Routing Tree
+    #[serde(skip)]
+    pub route: Option<String>,
+    /// Recipients that were selected by the Routing Tree
+    #[serde(skip)]
+    pub deliver_to: Vec<String>,
+    /// Sender Identity that was selected by the Routing Tree
+    #[serde(skip)]
+    pub sender: Option<SenderConfig>,
+    /// Message-ID assigned by the Thread Store
+    #[serde(skip)]
+    pub message_id: Option<String>,
+    /// Message-IDs of the earlier Emails in the Thread for `In-Reply-To` and `References`
+    /// The first Entry is the Root and the last Entry the Parent of this Email
+    #[serde(skip)]
+    pub references: Vec<String>,
 }
 
 /// Structure for Email Sending Results
@@ -45,6 +116,8 @@ pub struct EmailData {
 pub struct EmailResponse {
     pub status: String,
     pub report: String,
+    #[serde(default)]
+    pub job: String,
 }
 
 /// Structure for Email Sending Errors
@@ -52,15 +125,253 @@ pub struct EmailResponse {
 pub struct EmailError {
     status: String,
     report: String,
+    #[serde(default)]
+    kind: EmailErrorKind,
+    /// Seconds after which the Email can be sent again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
+}
+
+/// The Stage of the Sending that failed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailErrorKind {
+    Header,
+    Tls,
+    Build,
+    MessageId,
+    Dkim,
+    SmtpClient,
+    SmtpTransport,
+    /// The SMTP Relay refused the Email permanently with a 5xx Response
+    SmtpRejected,
+    /// The Email Workers did not take the Email
+    Mailbox,
+    /// The Email Worker panicked and was restarted
+    WorkerPanic,
+    /// The Circuit Breaker refused the Email because the SMTP Relay is failing
+    CircuitOpen,
+    /// The Mailbox of the Email Workers is full
+    MailboxFull,
+    /// The Email was not answered within the `send_timeout`
+    Timeout,
 }
 
 //==============================================================================
 // Structure EmailData Implementation
 
+impl EmailData {
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Splits the `to` Field into the single Recipient Addresses
+    pub fn recipients(&self) -> Vec<String> {
+        self.to
+            .split(',')
+            .map(|addr| addr.trim().to_owned())
+            .filter(|addr| !addr.is_empty())
+            .collect()
+    }
+
+    /// Returns the Recipients selected by the Routing Tree
+    /// or the requested Recipients if the Email was not routed
+    pub fn delivery_recipients(&self) -> Vec<String> {
+        if self.deliver_to.is_empty() {
+            self.recipients()
+        } else {
+            self.deliver_to.clone()
+        }
+    }
+
+    /// Returns the Addresses the Email is delivered to: the Recipients selected
+    /// by the Routing Tree or else the configured `default_address` as in `EmailSender::deliver`
+    pub fn delivery_addresses(&self, default_address: &str) -> Vec<String> {
+        if !self.deliver_to.is_empty() {
+            self.deliver_to.clone()
+        } else if !default_address.is_empty() {
+            vec![default_address.to_owned()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Identifies the Alert by the Caller supplied Fingerprint
+    /// or by the SHA-256 Hash of Subject, Recipients and Message
+    pub fn fingerprint_key(&self) -> String {
+        match &self.fingerprint {
+            Some(fingerprint) if !fingerprint.trim().is_empty() => fingerprint.trim().to_owned(),
+            _ => {
+                let mut hasher = Sha256::new();
+
+                hasher.update(self.subject.as_bytes());
+                hasher.update(b"\n");
+                hasher.update(self.recipients().join(",").to_lowercase().as_bytes());
+                hasher.update(b"\n");
+                hasher.update(self.message.as_bytes());
+
+                hex::encode(hasher.finalize())
+            }
+        }
+    }
+
+    /// Detects the Resolution of an Alert by its `status` Label
+    /// or by the `[RESOLVED]` Prefix of the Grafana Subject
+    pub fn is_resolved(&self) -> bool {
+        match self.labels.get("status") {
+            Some(status) => status.eq_ignore_ascii_case("resolved"),
+            None => self
+                .subject
+                .trim_start()
+                .to_uppercase()
+                .starts_with("[RESOLVED"),
+        }
+    }
+
+    /// Returns the Reply-To Address: the requested `reply_to`
+    /// or else the `from` Address if it is a valid Address
+    pub fn reply_address(&self) -> Option<String> {
+        match &self.reply_to {
+            Some(reply_to) => Some(reply_to.trim().to_owned()),
+            None => {
+                let from = self.from.trim();
+
+                validate_address(from).ok().map(|_| from.to_owned())
+            }
+        }
+    }
+
+    /// Identifies the Caller by the API Key Name or by the Client Address
+    pub fn caller_name(&self) -> String {
+        self.caller
+            .clone()
+            .or_else(|| self.client_address.clone())
+            .unwrap_or_else(|| String::from("anonymous"))
+    }
+}
+
+//==============================================================================
+// Structure EmailError Implementation
+
+impl EmailError {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(kind: EmailErrorKind, report: String) -> Self {
+        Self {
+            status: String::from("failed"),
+            report,
+            kind,
+            retry_after: None,
+        }
+    }
+
+    pub fn with_retry_after(mut self, retry_after: u64) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn kind(&self) -> EmailErrorKind {
+        self.kind
+    }
+
+    pub fn report(&self) -> &str {
+        &self.report
+    }
+
+    pub fn retry_after(&self) -> Option<u64> {
+        self.retry_after
+    }
+
+    /// Classifies the Error by the Stage of the Sending that failed
+    pub fn class(&self) -> &'static str {
+        self.kind.as_str()
+    }
+}
+
+//==============================================================================
+// Enum EmailErrorKind Implementation
+
+impl Default for EmailErrorKind {
+    fn default() -> Self {
+        EmailErrorKind::Mailbox
+    }
+}
+
+impl EmailErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailErrorKind::Header => "header",
+            EmailErrorKind::Tls => "tls",
+            EmailErrorKind::Build => "build",
+            EmailErrorKind::MessageId => "message_id",
+            EmailErrorKind::Dkim => "dkim",
+            EmailErrorKind::SmtpClient => "smtp_client",
+            EmailErrorKind::SmtpTransport => "smtp_transport",
+            EmailErrorKind::SmtpRejected => "smtp_rejected",
+            EmailErrorKind::Mailbox => "mailbox",
+            EmailErrorKind::WorkerPanic => "worker_panic",
+            EmailErrorKind::CircuitOpen => "circuit_open",
+            EmailErrorKind::MailboxFull => "mailbox_full",
+            EmailErrorKind::Timeout => "timeout",
+        }
+    }
+
+    /// Tells whether the SMTP Relay could not be reached or answered with a transient Error
+    /// A permanent Rejection concerns only the one Email and not the Relay
+    pub fn is_relay_failure(&self) -> bool {
+        matches!(
+            self,
+            EmailErrorKind::SmtpClient | EmailErrorKind::SmtpTransport | EmailErrorKind::Timeout
+        )
+    }
+}
+
 impl Message for EmailData {
     type Result = Result<EmailResponse, EmailError>;
 }
 
+/// Message to hand an Email to the Email Workers
+/// The Email stays in flight until the Worker has answered even when the Request
+/// which sent it is dropped
+pub struct Delivery {
+    pub email: EmailData,
+    in_flight: InFlightGuard,
+}
+
+impl Message for Delivery {
+    type Result = Result<EmailResponse, EmailError>;
+}
+
+/// Message to check that a Worker answers and that the SMTP Relay accepts a Connection
+#[derive(Debug, Message)]
+#[rtype(result = "Result<String, String>")]
+pub struct RelayProbe {
+    pub timeout: Duration,
+}
+
+/// Counter to keep Job Identifiers unique within the same Second
+static JOB_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Creates a new Job Identifier from the current Time and a Counter
+pub fn new_job_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    format!(
+        "{:x}-{:04x}",
+        now,
+        JOB_COUNTER.fetch_add(1, Ordering::Relaxed) & 0xffff
+    )
+}
+
 //==============================================================================
 // Structure EmailSender Declaration
 
@@ -68,6 +379,11 @@ impl Message for EmailData {
 // Define actor
 pub struct EmailSender {
     config: SMTPConfig,
+    dkim: Option<DkimSigner>,
+    metrics: Option<Metrics>,
+    /// Number of the Worker in the `SyncArbiter` reported to the Monitor
+    worker: usize,
+    monitor: Option<Addr<WorkerMonitor>>,
 }
 
 //==============================================================================
@@ -91,12 +407,20 @@ impl EmailSender {
     pub fn new() -> Self {
         Self {
             config: SMTPConfig::new(),
+            dkim: None,
+            metrics: None,
+            worker: 0,
+            monitor: None,
         }
     }
 
     pub fn from_config(config: &SMTPConfig) -> Self {
         Self {
             config: config.clone(),
+            dkim: load_signer(config),
+            metrics: None,
+            worker: 0,
+            monitor: None,
         }
     }
 
@@ -106,6 +430,27 @@ impl EmailSender {
 
     pub fn set_config(&mut self, config: &SMTPConfig) {
         self.config = config.clone();
+        self.dkim = load_signer(config);
+    }
+
+    /// Reports the Duration of the SMTP Transactions to the Metrics
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.metrics = Some(metrics.clone());
+    }
+
+    /// Reports the State of this Worker to the Monitor
+    pub fn set_monitor(&mut self, worker: usize, monitor: Addr<WorkerMonitor>) {
+        self.worker = worker;
+        self.monitor = Some(monitor);
+    }
+
+    fn report(&self, event: WorkerEvent) {
+        if let Some(monitor) = &self.monitor {
+            monitor.do_send(WorkerReport {
+                worker: self.worker,
+                event,
+            });
+        }
     }
 }
 
@@ -114,12 +459,14 @@ impl Actor for EmailSender {
     type Context = SyncContext<Self>;
 
     fn started(&mut self, _ctx: &mut Self::Context) {
-        println!("Email Sender Actor is alive");
-        println!("smtp config: {:?}", self.config);
+        info!("Email Sender Actor {} is alive", self.worker);
+        debug!("smtp config: {:?}", self.config);
+        self.report(WorkerEvent::Started);
     }
 
     fn stopped(&mut self, _ctx: &mut Self::Context) {
-        println!("Email Sender Actor is stopped");
+        info!("Email Sender Actor {} is stopped", self.worker);
+        self.report(WorkerEvent::Stopped);
     }
 }
 
@@ -127,50 +474,255 @@ impl Actor for EmailSender {
 impl Handler<EmailData> for EmailSender {
     type Result = Result<EmailResponse, EmailError>;
 
-    fn handle(&mut self, mail: EmailData, _ctx: &mut Self::Context) -> Self::Result {
-        println!("Email Data: '{:?}'", &mail);
+    fn handle(&mut self, mail: EmailData, ctx: &mut Self::Context) -> Self::Result {
+        // The Worker Thread logs with the Request ID of the Email
+        let request_id = mail.request_id.clone();
+
+        self.report(WorkerEvent::Busy);
+
+        let outcome = logging::with_request_id(request_id.as_deref(), || {
+            panic::catch_unwind(AssertUnwindSafe(|| self.deliver(mail)))
+        });
 
+        match outcome {
+            Ok(result) => {
+                self.report(match &result {
+                    Ok(_) => WorkerEvent::Succeeded,
+                    Err(e) => WorkerEvent::Failed(e.report.clone()),
+                });
+
+                result
+            }
+            Err(cause) => {
+                let e = EmailError::new(
+                    EmailErrorKind::WorkerPanic,
+                    format!(
+                        "Sending Error - Worker: Email Sender {} panicked: '{}'",
+                        self.worker,
+                        panic_message(&*cause)
+                    ),
+                );
+
+                logging::with_request_id(request_id.as_deref(), || {
+                    error!("{}; restarting ...", e.report)
+                });
+
+                if let Some(metrics) = &self.metrics {
+                    metrics.observe_restart();
+                }
+
+                self.report(WorkerEvent::Panicked(e.report.clone()));
+
+                // The SyncContext replaces the stopped Actor with a new one from the Factory
+                ctx.stop();
+
+                Err(e)
+            }
+        }
+    }
+}
+
+impl EmailSender {
+    /*----------------------------------------------------------------------------
+     * Sending Methods
+     */
+
+    /// Builds the Email and sends it through the SMTP Relay
+    fn deliver(&mut self, mail: EmailData) -> Result<EmailResponse, EmailError> {
+        info!(
+            "Email Job '{}': sending to {} Recipients ...",
+            mail.job,
+            mail.delivery_addresses(&self.config.email_address).len()
+        );
+
+        if logging::log_bodies() {
+            debug!("Email Data: '{:?}'", &mail);
+        }
+
+        let via: Vec<&str> = mail
+            .caller
+            .iter()
+            .chain(mail.client_address.iter())
+            .map(String::as_str)
+            .collect();
+        // The configured Headers come first and the requested Headers can override them
+        let mut headers: Vec<(String, String)> = self
+            .config
+            .headers
+            .iter()
+            .filter(|header| {
+                !mail
+                    .headers
+                    .keys()
+                    .any(|n| n.eq_ignore_ascii_case(&header.name))
+            })
+            .map(|header| (header.name.clone(), header.value.clone()))
+            .chain(mail.headers.clone())
+            .collect();
+
+        if !via.is_empty() {
+            headers.push((String::from("X-Alert-Caller"), via.join(", ")));
+        }
+
+        if let Some(request_id) = &mail.request_id {
+            headers.push((String::from(REQUEST_ID_HEADER), request_id.clone()));
+        }
+
+        let reply_to = mail.reply_address();
+        let checked = headers
+            .iter()
+            .try_for_each(|(name, value)| validate_header(name, value))
+            .and_then(|_| validate_header("Subject", &mail.subject))
+            .and_then(|_| match &reply_to {
+                Some(reply_to) => validate_address(reply_to),
+                None => Ok(()),
+            });
+
+        if let Err(e) = checked {
+            return Err(EmailError::new(
+                EmailErrorKind::Header,
+                format!("Sending Error - Header: '{}'", e),
+            ));
+        }
+
+        let connector = match TlsConnector::new() {
+            Ok(connector) => connector,
+            Err(e) => {
+                return Err(EmailError::new(
+                    EmailErrorKind::Tls,
+                    format!("Sending Error - TlsConnector: '{:?}'", e),
+                ))
+            }
+        };
         let security = ClientSecurity::Required(ClientTlsParameters::new(
             self.config.host.clone(),
-            TlsConnector::new().unwrap(),
+            connector,
         ));
         let smtp_url = self.config.host.clone() + ":" + self.config.port.as_str();
 
         match SmtpClient::new(smtp_url, security) {
             Ok(smtp) => {
                 let mut mailer = SmtpTransport::new(
-                    smtp.hello_name(ClientId::hostname())
+                    smtp.hello_name(hello_name(&self.config))
                         .credentials(Credentials::new(
                             self.config.login.clone(),
                             self.config.password.clone(),
                         ))
                         .authentication_mechanism(Mechanism::Login)
-                        .timeout(Some(Duration::new(15, 0))),
+                        // lettre applies the Timeout to the Connection and to each Command
+                        .timeout(Some(Duration::from_secs(self.config.command_timeout))),
                 );
 
-                let message = mail.message.as_bytes().to_owned();
-
-                println!("send message: '{:?}'", &message);
+                // The Route can select another Sender Identity
+                let (sender_address, sender_name) = match &mail.sender {
+                    Some(sender) => (sender.email_address.as_str(), sender.full_name.as_str()),
+                    None => (
+                        self.config.email_address.as_str(),
+                        self.config.full_name.as_str(),
+                    ),
+                };
 
-                let email = EmailBuilder::new()
+                let mut builder = EmailBuilder::new()
                     // Addresses can be specified by the tuple (email, alias)
                     // ... or by an address only
-                    .from((
-                        self.config.email_address.as_str(),
-                        self.config.full_name.as_str(),
-                    ))
-                    .header(Header::new("X-Forward-From".to_owned(), mail.from.clone()))
-                    .to((
+                    .from((sender_address, sender_name));
+
+                if let Some(reply_to) = reply_to {
+                    builder = builder.reply_to(reply_to);
+                }
+
+                for (name, value) in headers {
+                    builder = builder.header(Header::new(name, value));
+                }
+
+                // Only Recipients selected by the Routing Tree are used
+                // otherwise the Email goes to the configured Address
+                if mail.deliver_to.is_empty() {
+                    builder = builder.to((
                         self.config.email_address.as_str(),
                         self.config.full_name.as_str(),
-                    ))
+                    ));
+                } else {
+                    for recipient in mail.deliver_to.iter() {
+                        builder = builder.to(recipient.as_str());
+                    }
+                }
+
+                builder = builder
                     .subject(mail.subject.as_str())
-                    .text(mail.message.as_str())
-                    .build()
-                    .unwrap();
+                    .text(mail.message.as_str());
+
+                // The Email replies to the last Email of its Thread
+                if let Some(parent) = mail.references.last() {
+                    builder = builder.in_reply_to(parent.clone());
+                }
+
+                for reference in mail.references.iter() {
+                    builder = builder.references(reference.clone());
+                }
+
+                if mail.duplicates > 0 {
+                    builder = builder.header(Header::new(
+                        "X-Alert-Duplicates".to_owned(),
+                        mail.duplicates.to_string(),
+                    ));
+                }
+
+                let mut email: SendableEmail = match builder.build() {
+                    Ok(email) => email.into(),
+                    Err(e) => {
+                        return Err(EmailError::new(
+                            EmailErrorKind::Build,
+                            format!("Sending Error - EmailBuilder: '{:?}'", e),
+                        ))
+                    }
+                };
+
+                if let Some(message_id) = &mail.message_id {
+                    email = match replace_message_id(email, message_id) {
+                        Ok(email) => email,
+                        Err(e) => {
+                            return Err(EmailError::new(
+                                EmailErrorKind::MessageId,
+                                format!("Sending Error - Message-ID: '{:?}'", e),
+                            ))
+                        }
+                    };
+                }
+
+                // The Signature must cover the final Headers
+                match &self.dkim {
+                    Some(signer) => {
+                        email = match signer.sign_email(email) {
+                            Ok(email) => email,
+                            Err(e) => {
+                                return Err(EmailError::new(
+                                    EmailErrorKind::Dkim,
+                                    format!("Sending Error - DKIM: '{:?}'", e),
+                                ))
+                            }
+                        };
+                    }
+                    // The Email is not sent unsigned when the configured Key could not be loaded
+                    None if self.config.dkim.enabled => {
+                        return Err(EmailError::new(
+                            EmailErrorKind::Dkim,
+                            String::from("Sending Error - DKIM: Signer is not loaded"),
+                        ))
+                    }
+                    None => {}
+                }
+
+                let started = Instant::now();
 
                 // Send the email via remote relay
-                match mailer.send(email.into()) {
+                let result = mailer.send(email);
+
+                if let Some(metrics) = &self.metrics {
+                    metrics.observe_smtp_latency(started.elapsed());
+                }
+
+                match result {
                     Ok(res) => {
                         mailer.close();
 
@@ -180,34 +732,241 @@ impl Handler<EmailData> for EmailSender {
                                 "Email was sent with [{:?}]: {:?}",
                                 res.code, res.message
                             ),
+                            job: mail.job.clone(),
                         })
                     }
+                    Err(SmtpError::Permanent(response)) => {
+                        mailer.close();
+
+                        Err(EmailError::new(
+                            EmailErrorKind::SmtpRejected,
+                            format!(
+                                "Sending Error - SmtpRejected: [{:?}]: {:?}",
+                                response.code, response.message
+                            ),
+                        ))
+                    }
                     Err(e) => {
                         mailer.close();
 
-                        Err(EmailError {
-                            status: String::from("failed"),
-                            report: format!("Sending Error - SmtpTransport: '{:?}'", e),
-                        })
+                        Err(EmailError::new(
+                            EmailErrorKind::SmtpTransport,
+                            format!("Sending Error - SmtpTransport: '{:?}'", e),
+                        ))
                     }
                 }
             }
-            Err(e) => Err(EmailError {
-                status: String::from("failed"),
-                report: format!("Sending Error - SmtpClient: '{:?}'", e),
-            }),
+            Err(e) => Err(EmailError::new(
+                EmailErrorKind::SmtpClient,
+                format!("Sending Error - SmtpClient: '{:?}'", e),
+            )),
         }
     }
 }
 
+/// Define handler for the `Delivery` Message
+impl Handler<Delivery> for EmailSender {
+    type Result = Result<EmailResponse, EmailError>;
+
+    fn handle(&mut self, msg: Delivery, ctx: &mut Self::Context) -> Self::Result {
+        let Delivery { email, in_flight } = msg;
+        let result = <Self as Handler<EmailData>>::handle(self, email, ctx);
+
+        drop(in_flight);
+
+        result
+    }
+}
+
+/// Define handler for the `RelayProbe` Message
+impl Handler<RelayProbe> for EmailSender {
+    type Result = Result<String, String>;
+
+    fn handle(&mut self, msg: RelayProbe, _ctx: &mut Self::Context) -> Self::Result {
+        probe_relay(&self.config, msg.timeout)
+    }
+}
+
+/// Structure for the Emails which were handed to the Email Workers and are not answered yet
+/// They are awaited at the Shutdown and the remaining ones are kept in the Outbox
+#[derive(Clone, Default)]
+pub struct InFlight {
+    state: Arc<Mutex<InFlightState>>,
+}
+
+#[derive(Default)]
+struct InFlightState {
+    next: u64,
+    emails: BTreeMap<u64, EmailData>,
+}
+
+/// Structure for the reported Depth of the Email Queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStatus {
+    /// Emails which wait for or are processed by the Email Workers
+    pub depth: usize,
+    /// Limit of the Depth where 0 means no Limit
+    pub capacity: usize,
+    /// Emails which are kept in the Outbox
+    pub outbox: usize,
+}
+
+/// Removes the Email from the In-Flight Emails when it is answered
+pub struct InFlightGuard {
+    in_flight: InFlight,
+    key: u64,
+}
+
 #[derive(Clone)]
 pub struct EmailLink {
     addr: Addr<EmailSender>,
+    metrics: Option<Metrics>,
+    in_flight: InFlight,
+    mailbox: MailboxConfig,
+    send_timeout: Option<Duration>,
+    circuit: Option<CircuitBreaker>,
+    outbox: Option<Outbox>,
+}
+
+impl InFlight {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    pub fn enter(&self, email: &EmailData) -> InFlightGuard {
+        let mut state = self.lock();
+
+        self.insert(&mut state, email)
+    }
+
+    /// Enters the Email unless `capacity` Emails are in flight already
+    /// A `capacity` of 0 means no Limit
+    pub fn try_enter(&self, email: &EmailData, capacity: usize) -> Option<InFlightGuard> {
+        let mut state = self.lock();
+
+        if capacity > 0 && state.emails.len() >= capacity {
+            return None;
+        }
+
+        Some(self.insert(&mut state, email))
+    }
+
+    fn insert(&self, state: &mut InFlightState, email: &EmailData) -> InFlightGuard {
+        let key = state.next;
+
+        state.next += 1;
+        state.emails.insert(key, email.clone());
+
+        InFlightGuard {
+            in_flight: self.clone(),
+            key,
+        }
+    }
+
+    /// Waits until all Emails are answered or the Deadline has passed
+    /// and returns the Emails which are still not answered
+    pub async fn drain(&self, deadline: Instant) -> Vec<EmailData> {
+        while !self.is_empty() && Instant::now() < deadline {
+            let wait = deadline.saturating_duration_since(Instant::now());
+
+            actix_rt::time::delay_for(wait.min(Duration::from_millis(100))).await;
+        }
+
+        self.lock().emails.values().cloned().collect()
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn len(&self) -> usize {
+        self.lock().emails.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().emails.is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InFlightState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.lock().emails.remove(&self.key);
+    }
 }
 
 impl EmailLink {
     pub fn new(addr: Addr<EmailSender>) -> Self {
-        Self { addr }
+        Self {
+            addr,
+            metrics: None,
+            in_flight: InFlight::new(),
+            mailbox: MailboxConfig::new(),
+            send_timeout: None,
+            circuit: None,
+            outbox: None,
+        }
+    }
+
+    /// Reports the Mailbox Depth and the Send Outcomes to the Metrics
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.metrics = Some(metrics.clone());
+    }
+
+    /// Limits the Emails which wait for or are processed by the Email Workers
+    pub fn set_mailbox(&mut self, mailbox: &MailboxConfig) {
+        self.mailbox = mailbox.clone();
+    }
+
+    /// Stops waiting for the Email Worker after the Timeout
+    pub fn set_send_timeout(&mut self, send_timeout: Duration) {
+        self.send_timeout = Some(send_timeout);
+    }
+
+    /// Guards the SMTP Relay with the Circuit Breaker
+    pub fn set_circuit(&mut self, circuit: &CircuitBreaker) {
+        self.circuit = Some(circuit.clone());
+    }
+
+    /// Keeps the Emails in the Outbox while the Circuit is open in `queue` Mode
+    pub fn set_outbox(&mut self, outbox: &Outbox) {
+        self.outbox = Some(outbox.clone());
+    }
+
+    /// Emails which are queued for or sent by the Email Workers right now
+    pub fn in_flight(&self) -> &InFlight {
+        &self.in_flight
+    }
+
+    pub fn circuit(&self) -> Option<&CircuitBreaker> {
+        self.circuit.as_ref()
+    }
+
+    /// Tells whether the Mailbox of the Email Workers can take another Email
+    pub fn has_capacity(&self) -> bool {
+        self.mailbox.capacity == 0 || self.in_flight.len() < self.mailbox.capacity
+    }
+
+    pub fn queue_status(&self) -> QueueStatus {
+        QueueStatus {
+            depth: self.in_flight.len(),
+            capacity: self.mailbox.capacity,
+            outbox: self.outbox.as_ref().map(Outbox::len).unwrap_or(0),
+        }
     }
 
     pub fn send_email(
@@ -215,16 +974,154 @@ impl EmailLink {
         email: EmailData,
     ) -> impl Future<Output = Result<EmailResponse, EmailError>> + 'static {
         let sender = self.addr.clone();
+        let metrics = self.metrics.clone();
+        let in_flight = self.in_flight.try_enter(&email, self.mailbox.capacity);
+        let mailbox = self.mailbox.clone();
+        let circuit = self.circuit.clone();
+        let outbox = self.outbox.clone();
+        let send_timeout = self.send_timeout;
         async move {
-            match sender.send(email).await {
-                Ok(rs) => rs,
-                Err(e) => Err(EmailError {
-                    status: String::from("failed"),
-                    report: format!("Sending Error: '{:?}'", e),
-                }),
+            let in_flight = match in_flight {
+                Some(in_flight) => in_flight,
+                None => {
+                    if mailbox.overflow == "outbox" {
+                        if let Some(rs) = keep_email(
+                            outbox.as_ref(),
+                            &email,
+                            "Email Workers are busy; Email is kept in the Outbox",
+                        ) {
+                            observe_outcome(metrics.as_ref(), &rs.status);
+
+                            return Ok(rs);
+                        }
+                    }
+
+                    observe_outcome(metrics.as_ref(), EmailErrorKind::MailboxFull.as_str());
+
+                    return Err(EmailError::new(
+                        EmailErrorKind::MailboxFull,
+                        format!(
+                            "Sending Error - Mailbox: {} Emails are waiting; retry in {}s",
+                            mailbox.capacity, mailbox.retry_after
+                        ),
+                    )
+                    .with_retry_after(mailbox.retry_after));
+                }
+            };
+
+            // The Permit releases the Probe when the Request is dropped while it is sent
+            let permit = match &circuit {
+                Some(circuit) => match circuit.enter() {
+                    Ok(permit) => Some(permit),
+                    Err(wait) => {
+                        let retry_after = wait.as_secs() + 1;
+
+                        if circuit.is_queueing() {
+                            if let Some(rs) = keep_email(
+                                outbox.as_ref(),
+                                &email,
+                                "SMTP Relay is unavailable; Email is kept in the Outbox",
+                            ) {
+                                observe_outcome(metrics.as_ref(), &rs.status);
+
+                                return Ok(rs);
+                            }
+                        }
+
+                        observe_outcome(metrics.as_ref(), EmailErrorKind::CircuitOpen.as_str());
+
+                        return Err(EmailError::new(
+                            EmailErrorKind::CircuitOpen,
+                            format!(
+                                "Sending Error - Circuit: SMTP Relay is unavailable; retry in {}s",
+                                retry_after
+                            ),
+                        )
+                        .with_retry_after(retry_after));
+                    }
+                },
+                None => None,
+            };
+
+            // The SyncArbiter does not expose its Mailbox so the pending Emails are counted here
+            let pending = metrics.as_ref().map(Metrics::enter_mailbox);
+            let delivery = sender.send(Delivery { email, in_flight });
+            let answer = match send_timeout {
+                Some(limit) => actix_rt::time::timeout(limit, delivery).await.ok(),
+                None => Some(delivery.await),
+            };
+            let result = match answer {
+                Some(Ok(rs)) => rs,
+                Some(Err(e)) => Err(EmailError::new(
+                    EmailErrorKind::Mailbox,
+                    format!(
+                        "Sending Error - Mailbox: Email Workers are not reachable: '{}'",
+                        e
+                    ),
+                )),
+                // The Email Worker is not interrupted and can still send the Email
+                // so the Email stays in flight until the Worker answers
+                None => Err(EmailError::new(
+                    EmailErrorKind::Timeout,
+                    format!(
+                        "Sending Error - Timeout: Email was not sent within {}s",
+                        send_timeout.map(|limit| limit.as_secs()).unwrap_or(0)
+                    ),
+                )),
+            };
+
+            drop(pending);
+
+            if let Some(permit) = permit {
+                match &result {
+                    Ok(_) => {
+                        permit.record_success();
+                    }
+                    Err(e) if e.kind.is_relay_failure() => permit.record_failure(),
+                    // The Probe is released with the Permit
+                    Err(_) => drop(permit),
+                }
             }
+
+            match &result {
+                Ok(rs) => observe_outcome(metrics.as_ref(), &rs.status),
+                Err(e) => observe_outcome(metrics.as_ref(), e.class()),
+            }
+
+            result
         }
     }
+
+    /// Sends the Relay Probe to the next free Worker
+    pub fn probe_relay(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Result<String, String>, MailboxError>> + 'static {
+        self.addr.send(RelayProbe { timeout })
+    }
+}
+
+/// Keeps the Email in the Outbox when it is enabled
+fn keep_email(outbox: Option<&Outbox>, email: &EmailData, report: &str) -> Option<EmailResponse> {
+    let outbox = outbox.filter(|outbox| outbox.is_enabled())?;
+
+    match outbox.store(vec![email.clone()]) {
+        Ok(_) => Some(EmailResponse {
+            status: String::from("queued"),
+            report: report.to_owned(),
+            job: email.job.clone(),
+        }),
+        Err(e) => {
+            error!("Outbox: Job '{}' could not be kept: '{}'", email.job, e);
+            None
+        }
+    }
+}
+
+fn observe_outcome(metrics: Option<&Metrics>, outcome: &str) {
+    if let Some(metrics) = metrics {
+        metrics.observe_send(outcome);
+    }
 }
 
 pub async fn send_mail(link: &EmailLink, email: EmailData) -> Result<EmailResponse, EmailError> {
@@ -234,12 +1131,168 @@ pub async fn send_mail(link: &EmailLink, email: EmailData) -> Result<EmailRespon
 
     match email_future {
         Ok(rs) => {
-            println!("Email Result: '{:?}'", &rs);
+            info!("Email Result: '{:?}'", &rs);
             Ok(rs)
         }
         Err(e) => {
-            println!("Email Error: '{:?}'", &e);
+            error!("Email Error: '{:?}'", &e);
             Err(e)
         }
     }
 }
+
+/// Checks the Header Name and rejects Line Breaks and Control Characters in the Value
+pub fn validate_header(name: &str, value: &str) -> Result<(), String> {
+    if name.is_empty() || !name.bytes().all(|b| (33..=126).contains(&b) && b != b':') {
+        return Err(format!("Header Name '{}' is invalid", name.escape_debug()));
+    }
+
+    if value.contains('\r') || value.contains('\n') {
+        return Err(format!("Header '{}' must not contain Line Breaks", name));
+    }
+
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(format!(
+            "Header '{}' must not contain Control Characters",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the Value is a plain Address like `name@domain`
+pub fn validate_address(address: &str) -> Result<(), String> {
+    let mut parts = address.splitn(2, '@');
+    let valid = match (parts.next(), parts.next()) {
+        (Some(local), Some(domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && local
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c))
+                && domain
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '.')
+        }
+        _ => false,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Address '{}' is invalid", address.escape_debug()))
+    }
+}
+
+/// Only these Headers can be set by the Caller
+pub fn is_custom_header(name: &str) -> bool {
+    let name = name.to_lowercase();
+
+    name == "x-priority" || name == "importance" || name.starts_with("x-alert-")
+}
+
+/// Connects to the SMTP Relay and checks that it answers EHLO and NOOP
+/// No Email is sent and the Connection is closed with QUIT
+pub fn probe_relay(config: &SMTPConfig, timeout: Duration) -> Result<String, String> {
+    let smtp_url = config.host.clone() + ":" + config.port.as_str();
+    let mut client: InnerClient<NetworkStream> = InnerClient::new();
+
+    let connect_timeout = timeout.min(Duration::from_secs(config.connect_timeout));
+    let command_timeout = timeout.min(Duration::from_secs(config.command_timeout));
+
+    if let Err(e) = client.connect(&smtp_url, Some(connect_timeout), None) {
+        return Err(format!(
+            "Relay '{}': Connection failed: '{:?}'",
+            smtp_url, e
+        ));
+    }
+
+    if let Err(e) = client.set_timeout(Some(command_timeout)) {
+        client.close();
+
+        return Err(format!(
+            "Relay '{}': Timeout could not be set: '{:?}'",
+            smtp_url, e
+        ));
+    }
+
+    let result = client
+        .read_response()
+        .and_then(|_| client.command(EhloCommand::new(hello_name(config))))
+        .and_then(|_| client.command(NoopCommand));
+
+    let _ = client.command(QuitCommand);
+    client.close();
+
+    match result {
+        Ok(rs) => Ok(format!(
+            "Relay '{}' answered NOOP with [{:?}]",
+            smtp_url, rs.code
+        )),
+        Err(e) => Err(format!("Relay '{}': EHLO/NOOP failed: '{:?}'", smtp_url, e)),
+    }
+}
+
+/// Returns the configured HELO Name or else the Host Name
+fn hello_name(config: &SMTPConfig) -> ClientId {
+    if config.helo_name.trim().is_empty() {
+        ClientId::hostname()
+    } else {
+        ClientId::Domain(config.helo_name.trim().to_owned())
+    }
+}
+
+/// Extracts the Message of a caught Panic
+fn panic_message(cause: &(dyn Any + Send)) -> String {
+    if let Some(message) = cause.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = cause.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown cause")
+    }
+}
+
+/// Replaces the random Message-ID of the built Email with the assigned Message-ID
+fn replace_message_id(email: SendableEmail, message_id: &str) -> std::io::Result<SendableEmail> {
+    let envelope = email.envelope().clone();
+    let message = email.message_to_string()?;
+    let header_end = message.find("\r\n\r\n").unwrap_or(message.len());
+    let (headers, body) = message.split_at(header_end);
+    let headers: Vec<String> = headers
+        .split("\r\n")
+        .map(|line| {
+            if line.to_lowercase().starts_with("message-id:") {
+                format!("Message-ID: {}", message_id)
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect();
+
+    Ok(SendableEmail::new(
+        envelope,
+        message_id.trim_matches(|c| c == '<' || c == '>').to_owned(),
+        format!("{}{}", headers.join("\r\n"), body).into_bytes(),
+    ))
+}
+
+/// Loads the DKIM Signer if DKIM Signing is enabled
+/// A Key that cannot be loaded is reported and the Emails are refused
+/// The Key is checked before the Email Workers start
+fn load_signer(config: &SMTPConfig) -> Option<DkimSigner> {
+    if !config.dkim.enabled {
+        return None;
+    }
+
+    match DkimSigner::from_config(&config.dkim) {
+        Ok(signer) => Some(signer),
+        Err(e) => {
+            error!("Email Sender: DKIM Signer could not be loaded: '{}'", e);
+            None
+        }
+    }
+}
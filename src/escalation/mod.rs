@@ -0,0 +1,367 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Escalation Actor
+
+* This Module defines the Actor that escalates Alerts to the next Stage of their Policy
+* when they are not acknowledged or resolved in time
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use actix::fut::wrap_future;
+use actix::prelude::*;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::ack::AlertLinks;
+use crate::config::{EscalationConfig, EscalationPolicyConfig};
+use crate::email::{self, EmailData, EmailLink};
+use crate::routing::Matcher;
+use crate::silence::SilenceStore;
+use crate::store;
+
+const ESCALATION_FILE: &str = "escalations.json";
+
+//==============================================================================
+// Structure EscalationMessage Declaration
+
+/// Message to start the Escalation of an Alert that was sent
+/// The Alert is identified by its `threading::alert_key()`
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EscalationStart(pub String, pub EmailData);
+
+/// Message to stop the Escalation of a resolved Alert by its `threading::alert_key()`
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EscalationResolve(pub String);
+
+//==============================================================================
+// Structure PendingAlert Declaration
+
+/// Structure for an Alert that is waiting for its next Escalation Stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAlert {
+    /// Fingerprint of the Acknowledge Links of the Alert
+    pub fingerprint: String,
+    pub policy: String,
+    pub subject: String,
+    pub from: String,
+    pub message: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    pub started_at: u64,
+    /// Index of the Stage that is emailed next
+    pub stage: usize,
+    pub next_at: u64,
+    /// The Email of the Stage is being sent
+    #[serde(skip)]
+    pub sending: bool,
+}
+
+/// Structure for the persisted State of the Escalations
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EscalationState {
+    pub alerts: BTreeMap<String, PendingAlert>,
+}
+
+//==============================================================================
+// Structure EscalationActor Declaration
+
+/// Structure for the Escalation Timer
+pub struct EscalationActor {
+    config: EscalationConfig,
+    policies: Vec<(EscalationPolicyConfig, Vec<Matcher>)>,
+    link: EmailLink,
+    links: AlertLinks,
+    silences: Option<SilenceStore>,
+    file: PathBuf,
+    state: EscalationState,
+}
+
+//==============================================================================
+// Structure EscalationActor Implementation
+
+impl EscalationActor {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Loads the pending Escalations from the Data Directory
+    pub fn from_config(
+        config: &EscalationConfig,
+        data_directory: &str,
+        link: EmailLink,
+        links: AlertLinks,
+    ) -> std::io::Result<Self> {
+        let file = store::data_file(data_directory, ESCALATION_FILE);
+        let state: EscalationState = store::load(&file)?;
+
+        Ok(Self {
+            config: config.clone(),
+            policies: config
+                .policies
+                .iter()
+                .map(|p| {
+                    (
+                        p.clone(),
+                        p.matchers.iter().map(Matcher::from_config).collect(),
+                    )
+                })
+                .collect(),
+            link,
+            links,
+            silences: None,
+            file,
+            state,
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Sets the Silences which hold the Escalation of matching Alerts
+    pub fn set_silences(&mut self, silences: &SilenceStore) {
+        self.silences = Some(silences.clone());
+    }
+
+    /// Starts the Escalation of the Alert with the first Policy that matches it
+    pub fn register(&mut self, key: String, mail: EmailData) {
+        // A repeated Alert keeps its running Escalation
+        if self.state.alerts.contains_key(&key) {
+            return;
+        }
+
+        let policy = match self
+            .policies
+            .iter()
+            .find(|(_, matchers)| matchers.iter().all(|m| m.matches(&mail)))
+        {
+            Some((policy, _)) => policy,
+            None => return,
+        };
+        let first = match policy.stages.first() {
+            Some(stage) => stage,
+            None => return,
+        };
+        let now = store::unix_time();
+
+        info!("Escalation '{}': Policy '{}' started", key, policy.name);
+
+        self.state.alerts.insert(
+            key,
+            PendingAlert {
+                fingerprint: mail.fingerprint_key(),
+                policy: policy.name.clone(),
+                subject: mail.subject,
+                from: mail.from,
+                message: mail.message,
+                labels: mail.labels,
+                started_at: now,
+                stage: 0,
+                next_at: now.saturating_add(first.delay),
+                sending: false,
+            },
+        );
+        self.persist();
+    }
+
+    /// Stops the Escalation of the resolved Alert
+    pub fn resolve(&mut self, key: &str) {
+        if self.state.alerts.remove(key).is_some() {
+            info!("Escalation '{}': resolved", key);
+            self.persist();
+        }
+    }
+
+    /// Returns the Emails of the next Stage of each Alert whose Delay has run out
+    /// together with the Key of the Alert. The Stage advances with `complete()`.
+    /// Acknowledged Alerts and Alerts without further Stages are removed
+    /// and silenced Alerts wait until their Silence ends.
+    pub fn check(&mut self) -> Vec<(String, EmailData)> {
+        let now = store::unix_time();
+        let mut changed = false;
+        let mut mails = Vec::new();
+        let keys: Vec<String> = self.state.alerts.keys().cloned().collect();
+
+        for key in keys {
+            let acknowledged = match self.state.alerts.get(&key) {
+                Some(alert) => self.links.acknowledgement(&alert.fingerprint).is_some(),
+                None => continue,
+            };
+
+            if acknowledged {
+                info!("Escalation '{}': acknowledged", key);
+                self.state.alerts.remove(&key);
+                changed = true;
+                continue;
+            }
+
+            let alert = match self.state.alerts.get_mut(&key) {
+                Some(alert) if !alert.sending && alert.next_at <= now => alert,
+                _ => continue,
+            };
+            let stage = match self
+                .policies
+                .iter()
+                .find(|(p, _)| p.name == alert.policy)
+                .and_then(|(policy, _)| policy.stages.get(alert.stage))
+            {
+                Some(stage) => stage,
+                // The Policy was removed from the Configuration
+                None => {
+                    self.state.alerts.remove(&key);
+                    changed = true;
+                    continue;
+                }
+            };
+            let mail = EmailData {
+                subject: format!("[ESCALATION {}] {}", alert.stage + 1, alert.subject),
+                from: alert.from.clone(),
+                to: stage.recipients.join(", "),
+                deliver_to: stage.recipients.clone(),
+                message: alert.message.clone(),
+                labels: alert.labels.clone(),
+                fingerprint: Some(alert.fingerprint.clone()),
+                job: email::new_job_id(),
+                ..Default::default()
+            };
+
+            if let Some(silence) = self
+                .silences
+                .as_ref()
+                .and_then(|silences| silences.silenced_by(&mail))
+            {
+                info!(
+                    "Escalation '{}': Stage {} is held by Silence '{}'",
+                    key,
+                    alert.stage + 1,
+                    silence
+                );
+                continue;
+            }
+
+            info!(
+                "Escalation '{}': Stage {} to {:?}; sending Job '{}' ...",
+                key,
+                alert.stage + 1,
+                stage.recipients,
+                mail.job
+            );
+
+            alert.sending = true;
+            mails.push((key, mail));
+        }
+
+        if changed {
+            self.persist();
+        }
+
+        mails
+    }
+
+    /// Advances the Alert to its next Stage when the Email of its Stage was sent
+    /// A failed Stage is sent again at the next Check
+    pub fn complete(&mut self, key: &str, sent: bool) {
+        let now = store::unix_time();
+        let alert = match self.state.alerts.get_mut(key) {
+            Some(alert) => alert,
+            // The Alert was resolved while its Stage was sent
+            None => return,
+        };
+
+        alert.sending = false;
+
+        if !sent {
+            return;
+        }
+
+        alert.stage += 1;
+
+        let next = self
+            .policies
+            .iter()
+            .find(|(p, _)| p.name == alert.policy)
+            .and_then(|(policy, _)| policy.stages.get(alert.stage));
+
+        match next {
+            Some(stage) => alert.next_at = now.saturating_add(stage.delay),
+            None => {
+                self.state.alerts.remove(key);
+            }
+        }
+
+        self.persist();
+    }
+
+    /// Sends the due Stages and completes them with the Result of the Email Workers
+    fn escalate(&mut self, ctx: &mut Context<Self>) {
+        for (key, mail) in self.check() {
+            ctx.spawn(wrap_future::<_, Self>(self.link.send_email(mail)).map(
+                move |result, act, _ctx| {
+                    match &result {
+                        Ok(rs) => info!("Escalation '{}': {:?}", key, rs),
+                        Err(e) => error!("Escalation '{}': failed: {:?}", key, e),
+                    }
+
+                    act.complete(&key, result.is_ok());
+                },
+            ));
+        }
+    }
+
+    fn persist(&self) {
+        if let Err(e) = store::save(&self.file, &self.state) {
+            error!("Escalations: save to {:?} failed: '{}'", self.file, e);
+        }
+    }
+}
+
+impl Actor for EscalationActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        info!(
+            "Escalation Actor is alive; {} Alerts pending; checking every {} seconds",
+            self.state.alerts.len(),
+            self.config.check_interval
+        );
+
+        ctx.run_interval(
+            Duration::from_secs(self.config.check_interval.max(1)),
+            |act, ctx| {
+                act.escalate(ctx);
+            },
+        );
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        info!("Escalation Actor is stopped");
+    }
+}
+
+/// Define handler for `EscalationStart` Message
+impl Handler<EscalationStart> for EscalationActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: EscalationStart, _ctx: &mut Self::Context) -> Self::Result {
+        self.register(msg.0, msg.1);
+    }
+}
+
+/// Define handler for `EscalationResolve` Message
+impl Handler<EscalationResolve> for EscalationActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: EscalationResolve, _ctx: &mut Self::Context) -> Self::Result {
+        self.resolve(&msg.0);
+    }
+}
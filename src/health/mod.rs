@@ -0,0 +1,224 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Health Checks
+
+* This Module defines the Liveness and Readiness Checks of the Service
+* The Readiness checks the Email Workers and the SMTP Relay
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::HealthConfig;
+use crate::email::EmailLink;
+
+//==============================================================================
+// Structure HealthReport Declaration
+
+/// Structure for the Result of a single Check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// `up`, `down` or `unknown` if the Check could not be run
+    pub status: String,
+    pub details: String,
+    pub duration_ms: u64,
+}
+
+/// Structure for the Response of the Health Endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: String,
+    /// Unix Time of the Checks
+    pub checked_at: u64,
+    /// Whether the Report was taken from the Cache
+    pub cached: bool,
+    pub checks: BTreeMap<String, CheckResult>,
+}
+
+//==============================================================================
+// Structure HealthChecks Declaration
+
+/// Structure for the Health Checks shared by all HTTP Workers
+#[derive(Clone)]
+pub struct HealthChecks {
+    config: HealthConfig,
+    started: Instant,
+    readiness: Arc<Mutex<Option<(Instant, HealthReport)>>>,
+}
+
+//==============================================================================
+// Structure HealthReport Implementation
+
+impl HealthReport {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Builds the Report which is only `up` when all Checks are `up`
+    pub fn from_checks(checks: BTreeMap<String, CheckResult>) -> Self {
+        let status = if checks.values().all(|check| check.status == "up") {
+            "up"
+        } else {
+            "down"
+        };
+
+        Self {
+            status: String::from(status),
+            checked_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            cached: false,
+            checks,
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn is_up(&self) -> bool {
+        self.status == "up"
+    }
+}
+
+//==============================================================================
+// Structure HealthChecks Implementation
+
+impl HealthChecks {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &HealthConfig) -> Self {
+        Self {
+            config: config.clone(),
+            started: Instant::now(),
+            readiness: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Checks only that the Process is running and answers Requests
+    pub fn liveness(&self) -> HealthReport {
+        let mut checks = BTreeMap::new();
+
+        checks.insert(
+            String::from("process"),
+            CheckResult {
+                status: String::from("up"),
+                details: format!(
+                    "Process is running for {} Seconds",
+                    self.started.elapsed().as_secs()
+                ),
+                duration_ms: 0,
+            },
+        );
+
+        HealthReport::from_checks(checks)
+    }
+
+    /// Checks that an Email Worker answers, that the SMTP Relay accepts EHLO and NOOP
+    /// and that the Circuit Breaker is not open
+    /// The Result is cached for `cache_ttl` Seconds so that frequent Probes
+    /// do not open a Connection to the Relay each Time
+    pub async fn readiness(&self, link: &EmailLink) -> HealthReport {
+        if let Some(report) = self.cached() {
+            return report;
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout);
+        let started = Instant::now();
+        let probe = actix_rt::time::timeout(timeout, link.probe_relay(timeout)).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let check = |status: &str, details: String| CheckResult {
+            status: String::from(status),
+            details,
+            duration_ms,
+        };
+        let (sender, relay) = match probe {
+            Ok(Ok(relay)) => (
+                check("up", String::from("Email Worker answered")),
+                match relay {
+                    Ok(report) => check("up", report),
+                    Err(report) => check("down", report),
+                },
+            ),
+            Ok(Err(e)) => (
+                check("down", format!("Email Workers are not reachable: '{}'", e)),
+                check("unknown", String::from("Relay was not checked")),
+            ),
+            Err(_) => (
+                check(
+                    "down",
+                    format!("No Email Worker answered within {}s", self.config.timeout),
+                ),
+                check("unknown", String::from("Relay was not checked")),
+            ),
+        };
+        let mut checks = BTreeMap::new();
+
+        checks.insert(String::from("email_sender"), sender);
+        checks.insert(String::from("smtp_relay"), relay);
+
+        // The open Circuit refuses Emails even if the Relay answers the Probe again
+        // and the half-open Circuit lets only its Probe Email through
+        if let Some(circuit) = link.circuit().filter(|circuit| circuit.is_enabled()) {
+            let circuit = circuit.status();
+            let status = if circuit.state == "closed" {
+                "up"
+            } else {
+                "down"
+            };
+
+            checks.insert(
+                String::from("circuit_breaker"),
+                check(
+                    status,
+                    format!(
+                        "Circuit is {} after {} Relay Failures",
+                        circuit.state, circuit.failures
+                    ),
+                ),
+            );
+        }
+
+        let report = HealthReport::from_checks(checks);
+        let mut readiness = match self.readiness.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+
+        *readiness = Some((Instant::now(), report.clone()));
+
+        report
+    }
+
+    /// Returns the last Readiness if it is younger than `cache_ttl` Seconds
+    fn cached(&self) -> Option<HealthReport> {
+        let readiness = match self.readiness.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+        let ttl = Duration::from_secs(self.config.cache_ttl);
+
+        match readiness.as_ref() {
+            Some((checked, report)) if checked.elapsed() < ttl => Some(HealthReport {
+                cached: true,
+                ..report.clone()
+            }),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 /*
 * @author Bodo (Hugo) Barwich
-* @version 2024-02-04
+* @version 2026-10-18
 * @package Grafana Alerting
 * @subpackage Email Micro Service
 
@@ -18,24 +18,69 @@
 //#[macro_use]
 extern crate json;
 
+pub mod access;
+pub mod ack;
+pub mod auth;
+pub mod circuit;
 pub mod config;
+pub mod correlation;
+pub mod dedup;
+pub mod digest;
 pub mod email;
-pub mod ping;
+pub mod escalation;
+pub mod health;
+pub mod limit;
+pub mod logging;
+pub mod metrics;
+pub mod monitor;
+pub mod outbox;
+pub mod routing;
+pub mod shutdown;
+pub mod silence;
+pub mod store;
+pub mod threading;
+pub mod tls;
+pub mod validation;
 
 use std::env;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::Duration;
 
 use actix::sync::SyncArbiter;
-use actix_web::{error, web, App, Error, HttpResponse, HttpServer};
+use actix::{Actor, Addr};
+use actix_web::http::header;
+use actix_web::{error, web, App, Error, HttpRequest, HttpResponse, HttpServer};
 use futures_util::stream::StreamExt;
 //use mime;
 
 use json::JsonValue;
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 
 use actix_web::middleware::Logger;
 
+use access::{AccessFilter, ClientAddress};
+use ack::AlertLinks;
+use auth::{Authentication, Caller};
+use circuit::CircuitBreaker;
 use config::AppConfig;
-use email::{EmailData, EmailLink, EmailSender};
+use config::{MatcherConfig, ValidationConfig};
+use correlation::{Correlation, RequestId};
+use dedup::DedupCache;
+use digest::{DigestActor, DigestAdd, DigestFlush};
+use email::dkim::DkimSigner;
+use email::{EmailData, EmailErrorKind, EmailLink, EmailSender};
+use escalation::{EscalationActor, EscalationResolve, EscalationStart};
+use health::HealthChecks;
+use limit::RateLimiter;
+use metrics::{Metrics, RequestMetrics};
+use monitor::{worker_id, MonitorQuery, WorkerMonitor};
+use outbox::Outbox;
+use routing::Router;
+use silence::{SilenceRequest, SilenceState, SilenceStore};
+use threading::ThreadStore;
+use tls::CertificateResolver;
 
 const MAX_SIZE: usize = 262_144; // max payload size is 256k
 
@@ -83,7 +128,41 @@ pub async fn dispatch_home_page() -> HttpResponse {
 }
 
 /// This Handler reads the Request and parses it into EmailData object with serde
+/// The Request is answered with 504 when it is not finished within the `request_timeout`
 pub async fn send_email(
+    req: HttpRequest,
+    link: web::Data<EmailLink>,
+    payload: web::Payload,
+) -> Result<HttpResponse, Error> {
+    let request_timeout = match req.app_data::<web::Data<AppConfig>>() {
+        Some(config) => config.request_timeout,
+        None => 0,
+    };
+
+    if request_timeout == 0 {
+        return process_email(req, link, payload).await;
+    }
+
+    let deadline = Duration::from_secs(request_timeout);
+
+    match actix_rt::time::timeout(deadline, process_email(req, link, payload)).await {
+        Ok(result) => result,
+        Err(_) => {
+            warn!(
+                "email error: Request was not finished within {}s",
+                request_timeout
+            );
+
+            Ok(gateway_timeout(format!(
+                "Request was not finished within {}s",
+                request_timeout
+            )))
+        }
+    }
+}
+
+async fn process_email(
+    req: HttpRequest,
     link: web::Data<EmailLink>,
     mut payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
@@ -98,22 +177,197 @@ pub async fn send_email(
         body.extend_from_slice(&chunk);
     }
 
-    println!("got payload: '{:?}'", &body);
+    if logging::log_bodies() {
+        debug!("got payload: '{:?}'", &body);
+    }
+
+    // Verify the Webhook Signature before the Body is parsed
+    if let Some(config) = req.app_data::<web::Data<AppConfig>>() {
+        if let Err(e) = auth::verify_signature(&config.auth.signature, req.headers(), &body) {
+            warn!("signature error: '{}'", e);
+            return Err(e.into());
+        }
+    }
 
     // body is loaded, now we can deserialize serde-json
 
     match serde_json::from_slice::<EmailData>(&body) {
-        Ok(email) => {
+        Ok(mut email) => {
+            let validation = match req.app_data::<web::Data<AppConfig>>() {
+                Some(config) => config.validation.clone(),
+                None => ValidationConfig::new(),
+            };
+
+            if let Err(e) = validation::validate(&mut email, &validation) {
+                warn!("email invalid: '{}'", e);
+                return Err(e.into());
+            }
+
+            // The Caller was authenticated by the Authentication Middleware
+            email.caller = req.extensions().get::<Caller>().map(|c| c.name.clone());
+            // The Client Address was resolved by the AccessFilter Middleware
+            email.client_address = req
+                .extensions()
+                .get::<ClientAddress>()
+                .map(|a| a.to_string());
+
+            email.job = email::new_job_id();
+            // The Request ID was assigned by the Correlation Middleware
+            email.request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+            // The Alert is identified by its Subject before the Route Templates are applied
+            let fingerprint = email.fingerprint_key();
+            // The Escalation follows the Alert across its Status Changes like the Email Thread
+            let alert_key = threading::alert_key(&email);
+            let thread_key = threading::thread_key(&email);
+
+            // The Silences, Acknowledgements and Escalations match the Fingerprint from here on
+            email.fingerprint = Some(fingerprint.clone());
+
+            if let Some(router) = req.app_data::<web::Data<Router>>() {
+                if let Some(route) = router.apply(&mut email) {
+                    info!(
+                        "email route: '{}' -> {:?}",
+                        route.path.join(" > "),
+                        route.recipients
+                    );
+                }
+            }
+
+            let escalation = req.app_data::<web::Data<Addr<EscalationActor>>>();
+
+            // A Resolution stops the Escalation even if the Email itself is suppressed
+            if email.is_resolved() {
+                if let Some(escalation) = escalation {
+                    escalation.do_send(EscalationResolve(alert_key.clone()));
+                }
+            }
+
+            if let Some(silences) = req.app_data::<web::Data<SilenceStore>>() {
+                if let Some(rs) = silences.check(&email) {
+                    info!("email res: '{:?}'", rs);
+                    return Ok(HttpResponse::Ok().json(rs));
+                }
+            }
+
+            let links = req.app_data::<web::Data<AlertLinks>>();
+            let dedup = req.app_data::<web::Data<DedupCache>>();
+
+            if let Some(links) = links {
+                if let Some(rs) = links.check(&email, &fingerprint) {
+                    info!("email res: '{:?}'", rs);
+                    return Ok(HttpResponse::Ok().json(rs));
+                }
+            }
+
+            if let Some(cache) = dedup {
+                if let Some(rs) = cache.check(&mut email, &fingerprint) {
+                    info!("email res: '{:?}'", rs);
+                    return Ok(HttpResponse::Ok().json(rs));
+                }
+            }
+
+            // The Links are added after the Deduplication because their Tokens change
+            if let Some(links) = links {
+                links.decorate(&mut email, &fingerprint);
+            }
+
+            if let Some(limiter) = req.app_data::<web::Data<RateLimiter>>() {
+                // Unrouted Emails go to the configured Address whatever the Caller sent in `to`
+                let default_address = req
+                    .app_data::<web::Data<AppConfig>>()
+                    .map(|config| config.smtp.email_address.clone())
+                    .unwrap_or_default();
+
+                if let Err(e) = limiter
+                    .acquire(
+                        &email.delivery_addresses(&default_address),
+                        &email.caller_name(),
+                    )
+                    .await
+                {
+                    warn!("limit error: '{}'", e);
+
+                    if let Some(cache) = dedup {
+                        cache.forget(&fingerprint, &email.job);
+                    }
+
+                    return Err(e.into());
+                }
+            }
+
+            // Digested Alerts count against the Rate Limits; the Digest itself is threaded
+            if email.digest {
+                if let Some(digest) = req.app_data::<web::Data<Addr<DigestActor>>>() {
+                    return match digest.send(DigestAdd(email)).await {
+                        Ok(rs) => {
+                            info!("email res: '{:?}'", rs);
+                            Ok(HttpResponse::Ok().json(rs))
+                        }
+                        Err(e) => {
+                            error!("digest error: '{:?}'", e);
+                            Err(error::ErrorInternalServerError(format!(
+                                "Digest failed: '{}'\n",
+                                e
+                            )))
+                        }
+                    };
+                }
+            }
+
+            if let Some(threads) = req.app_data::<web::Data<ThreadStore>>() {
+                threads.assign_key(&mut email, thread_key);
+            }
+
+            let job = email.job.clone();
+            let pending = match escalation {
+                Some(escalation) if !email.is_resolved() => {
+                    Some((escalation, EscalationStart(alert_key, email.clone())))
+                }
+                _ => None,
+            };
+
             //Ok(HttpResponse::Ok().json(email)) // <- send response
             //match link_mutex.lock() {
             //Ok(link_lock) => {
             match email::send_mail(&link, email).await {
                 Ok(rs) => {
-                    println!("email res: '{:?}'", rs);
+                    info!("email res: '{:?}'", rs);
+
+                    if let Some((escalation, start)) = pending {
+                        escalation.do_send(start);
+                    }
+
                     Ok(HttpResponse::Ok().json(rs)) // <- send response
                 }
                 Err(e) => {
-                    println!("email error: '{:?}'", e);
+                    error!("email error: '{:?}'", e);
+
+                    if let Some(cache) = dedup {
+                        cache.forget(&fingerprint, &job);
+                    }
+
+                    if e.kind() == EmailErrorKind::Timeout {
+                        return Ok(gateway_timeout(e.report().to_owned()));
+                    }
+
+                    // The Caller can retry when the Relay or the Email Workers are available again
+                    if e.kind() == EmailErrorKind::CircuitOpen
+                        || e.kind() == EmailErrorKind::MailboxFull
+                    {
+                        return Ok(HttpResponse::ServiceUnavailable()
+                            .header(
+                                header::RETRY_AFTER,
+                                e.retry_after().unwrap_or(1).to_string(),
+                            )
+                            .json(ResponseData {
+                                title: String::from("Service Unavailable"),
+                                statuscode: 503,
+                                page: String::from("Send"),
+                                description: e.report().to_owned(),
+                            }));
+                    }
+
                     Err(error::ErrorBadRequest(format!(
                         "Sending failed: '{:?}'\n",
                         e
@@ -125,7 +379,7 @@ pub async fn send_email(
             //}
         }
         Err(e) => {
-            println!("json error: '{:?}'", e);
+            warn!("json error: '{:?}'", e);
             Err(error::ErrorBadRequest(format!(
                 "Request invalid: '{}'\n",
                 e
@@ -134,6 +388,233 @@ pub async fn send_email(
     }
 }
 
+fn gateway_timeout(description: String) -> HttpResponse {
+    HttpResponse::GatewayTimeout().json(ResponseData {
+        title: String::from("Gateway Timeout"),
+        statuscode: 504,
+        page: String::from("Send"),
+        description,
+    })
+}
+
+/// Handler to report the current State of the Rate Limits
+pub async fn dispatch_limits_request(limiter: web::Data<RateLimiter>) -> HttpResponse {
+    HttpResponse::Ok().json(limiter.status())
+}
+
+/// Handler to export the Metrics in the Prometheus Text Format
+pub async fn dispatch_metrics(metrics: web::Data<Metrics>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(metrics.render())
+}
+
+/// Handler to show the Route a Payload would take without sending it
+pub async fn dispatch_route_test(
+    router: web::Data<Router>,
+    email: web::Json<EmailData>,
+) -> HttpResponse {
+    match router.resolve(&email) {
+        Some(route) => HttpResponse::Ok().json(route),
+        None => HttpResponse::NotFound().json(ResponseData {
+            title: String::from("Route Test"),
+            statuscode: 404,
+            page: String::from("Route"),
+            description: String::from("Routing is not enabled"),
+        }),
+    }
+}
+
+/// Handler to create a new Silence
+pub async fn dispatch_silence_create(
+    silences: web::Data<SilenceStore>,
+    request: web::Json<SilenceRequest>,
+) -> Result<HttpResponse, Error> {
+    match silences.add(request.into_inner()) {
+        Ok(silence) => {
+            info!("silence created: '{:?}'", silence);
+            Ok(HttpResponse::Created().json(silence))
+        }
+        Err(e) => Err(error::ErrorBadRequest(format!(
+            "Silence invalid: '{}'\n",
+            e
+        ))),
+    }
+}
+
+/// Handler to list the active Silences and the suppressed Emails
+pub async fn dispatch_silence_list(silences: web::Data<SilenceStore>) -> HttpResponse {
+    HttpResponse::Ok().json(SilenceState {
+        silences: silences.list(),
+        suppressed: silences.suppressed().into_iter().collect(),
+    })
+}
+
+/// Handler to remove a Silence
+pub async fn dispatch_silence_delete(
+    silences: web::Data<SilenceStore>,
+    id: web::Path<String>,
+) -> HttpResponse {
+    match silences.remove(&id) {
+        Some(silence) => {
+            info!("silence removed: '{:?}'", silence);
+            HttpResponse::Ok().json(silence)
+        }
+        None => HttpResponse::NotFound().json(ResponseData {
+            title: String::from("Silence"),
+            statuscode: 404,
+            page: String::from("Silences"),
+            description: format!("Silence '{}' does not exist", id),
+        }),
+    }
+}
+
+/// Structure for the Query of an Acknowledge or Silence Link
+#[derive(Debug, Deserialize)]
+pub struct LinkQuery {
+    pub token: String,
+}
+
+/// Handler for the Acknowledge Link of an Alert Email
+pub async fn dispatch_alert_ack(
+    links: web::Data<AlertLinks>,
+    query: web::Query<LinkQuery>,
+) -> HttpResponse {
+    let token = match verify_link(&links, &query.token, "ack") {
+        Ok(token) => token,
+        Err(rs) => return rs,
+    };
+    let ack = links.acknowledge(&token.fingerprint);
+
+    info!("alert acknowledged: '{:?}'", ack);
+
+    link_page(
+        HttpResponse::Ok(),
+        "Alert acknowledged",
+        &format!(
+            "The Alert '{}' was acknowledged until {}.",
+            ack.fingerprint, ack.expires_at
+        ),
+    )
+}
+
+/// Handler for the Silence Link of an Alert Email
+pub async fn dispatch_alert_silence(
+    links: web::Data<AlertLinks>,
+    silences: web::Data<SilenceStore>,
+    query: web::Query<LinkQuery>,
+) -> HttpResponse {
+    let token = match verify_link(&links, &query.token, "silence") {
+        Ok(token) => token,
+        Err(rs) => return rs,
+    };
+    let request = SilenceRequest {
+        matchers: vec![MatcherConfig {
+            name: String::from("fingerprint"),
+            value: token.fingerprint.clone(),
+            regex: String::new(),
+        }],
+        starts_at: None,
+        ends_at: None,
+        duration: Some(links.silence_duration()),
+        created_by: String::from("link"),
+        comment: String::from("Silenced by the Link in the Alert Email"),
+    };
+
+    match silences.add(request) {
+        Ok(silence) => {
+            info!("silence created: '{:?}'", silence);
+
+            link_page(
+                HttpResponse::Ok(),
+                "Alert silenced",
+                &format!(
+                    "The Alert '{}' is silenced until {} by Silence '{}'.",
+                    token.fingerprint, silence.ends_at, silence.id
+                ),
+            )
+        }
+        Err(e) => link_page(
+            HttpResponse::BadRequest(),
+            "Silence failed",
+            &format!("The Silence could not be created: {}", e),
+        ),
+    }
+}
+
+/// Checks the Token of a clicked Link and builds the Error Page if it is invalid
+fn verify_link(
+    links: &AlertLinks,
+    token: &str,
+    action: &str,
+) -> Result<ack::LinkToken, HttpResponse> {
+    if !links.is_enabled() {
+        return Err(link_page(
+            HttpResponse::NotFound(),
+            "Link invalid",
+            "Alert Links are not enabled.",
+        ));
+    }
+
+    match links.verify(token) {
+        Ok(token) if token.action == action => Ok(token),
+        Ok(_) => Err(link_page(
+            HttpResponse::BadRequest(),
+            "Link invalid",
+            "The Link is not valid for this Action.",
+        )),
+        Err(e) => {
+            warn!("link error: '{}'", e);
+            Err(link_page(
+                HttpResponse::Forbidden(),
+                "Link invalid",
+                &format!("The Link is not valid: {}.", e),
+            ))
+        }
+    }
+}
+
+fn link_page(
+    mut builder: actix_web::dev::HttpResponseBuilder,
+    title: &str,
+    message: &str,
+) -> HttpResponse {
+    builder
+        .content_type("text/html; charset=utf-8")
+        .body(ack::confirmation_page(title, message))
+}
+
+/// Handler to send the collected Digests immediately
+pub async fn dispatch_digest_flush(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let digest = match req.app_data::<web::Data<Addr<DigestActor>>>() {
+        Some(digest) => digest,
+        None => {
+            return Ok(HttpResponse::NotFound().json(ResponseData {
+                title: String::from("Digest Flush"),
+                statuscode: 404,
+                page: String::from("Digest"),
+                description: String::from("Digest is not enabled"),
+            }))
+        }
+    };
+
+    match digest.send(DigestFlush).await {
+        Ok(count) => Ok(HttpResponse::Ok().json(ResponseData {
+            title: String::from("Digest Flush"),
+            statuscode: 200,
+            page: String::from("Digest"),
+            description: format!("Digest Flush [ OK ]; {} Digests dispatched", count),
+        })),
+        Err(e) => {
+            error!("digest error: '{:?}'", e);
+            Err(error::ErrorInternalServerError(format!(
+                "Digest failed: '{}'\n",
+                e
+            )))
+        }
+    }
+}
+
 /// This handler manually load request payload and parse json-rust
 async fn index_mjsonrust(body: web::Bytes) -> Result<HttpResponse, Error> {
     // body is loaded, now we can deserialize json-rust
@@ -147,21 +628,81 @@ async fn index_mjsonrust(body: web::Bytes) -> Result<HttpResponse, Error> {
         .body(injson.dump()))
 }
 
-pub async fn dispatch_ping_request() -> Result<HttpResponse, Error> {
-    println!("Request 'Ping': processing ...");
-    let ping_rs = ping::ping().await;
+/// Handler for the Liveness Probe which only checks the Process
+pub async fn dispatch_health_live(health: web::Data<HealthChecks>) -> HttpResponse {
+    HttpResponse::Ok().json(health.liveness())
+}
 
-    match ping_rs {
-        Ok(resp) => Ok(HttpResponse::Ok().json(ResponseData {
-            title: String::from("Ping Request"),
-            statuscode: 200,
-            page: String::from("Ping"),
-            description: format!("Ping Request [ OK ]; Message: '{}'", resp),
-        })),
+/// Handler for the Readiness Probe which checks the Email Workers and the SMTP Relay
+pub async fn dispatch_health_ready(
+    health: web::Data<HealthChecks>,
+    link: web::Data<EmailLink>,
+) -> HttpResponse {
+    let report = health.readiness(&link).await;
+
+    if report.is_up() {
+        HttpResponse::Ok().json(report)
+    } else {
+        warn!("readiness: '{:?}'", report);
+        HttpResponse::ServiceUnavailable().json(report)
+    }
+}
+
+/// Handler to check that the Worker Monitor answers and to summarise the Worker States
+pub async fn dispatch_ping_request(
+    monitor: web::Data<Addr<WorkerMonitor>>,
+) -> Result<HttpResponse, Error> {
+    debug!("Request 'Ping': processing ...");
+
+    match monitor.send(MonitorQuery).await {
+        Ok(status) => {
+            let idle = status
+                .workers
+                .iter()
+                .filter(|worker| worker.state == "idle")
+                .count();
 
+            Ok(HttpResponse::Ok().json(ResponseData {
+                title: String::from("Ping Request"),
+                statuscode: 200,
+                page: String::from("Ping"),
+                description: format!(
+                    "Ping Request [ OK ]; {} of {} Email Workers idle",
+                    idle,
+                    status.workers.len()
+                ),
+            }))
+        }
         Err(e) => {
-            println!("ping error: '{:?}'", e);
-            Err(error::ErrorBadRequest(format!("Request failed: '{}'\n", e)))
+            error!("ping error: '{:?}'", e);
+            Err(error::ErrorInternalServerError(format!(
+                "Request failed: '{}'\n",
+                e
+            )))
+        }
+    }
+}
+
+/// Handler to report the State of each Email Worker from the Worker Monitor
+/// The Depth of the Email Queue is reported when the Email Link is available
+pub async fn dispatch_worker_status(
+    req: HttpRequest,
+    monitor: web::Data<Addr<WorkerMonitor>>,
+) -> Result<HttpResponse, Error> {
+    match monitor.send(MonitorQuery).await {
+        Ok(mut status) => {
+            status.queue = req
+                .app_data::<web::Data<EmailLink>>()
+                .map(|link| link.queue_status());
+
+            Ok(HttpResponse::Ok().json(status))
+        }
+        Err(e) => {
+            error!("monitor error: '{:?}'", e);
+            Err(error::ErrorInternalServerError(format!(
+                "Request failed: '{}'\n",
+                e
+            )))
         }
     }
 }
@@ -171,12 +712,19 @@ pub async fn dispatch_ping_request() -> Result<HttpResponse, Error> {
 
 #[actix_web::main]
 pub async fn main() -> std::io::Result<()> {
-    std::env::set_var("RUST_LOG", "actix_web=info");
-    env_logger::init();
+    logging::init();
 
     let config = AppConfig::from_file();
 
-    println!("app config: {:?}", config);
+    logging::configure(&config.logging, &config.component);
+
+    //The Application does not start when a Request can time out while its Email is sent
+    config.check_timeouts()?;
+
+    info!(
+        "app config: project '{}'; {} Mail Workers; log level '{}'",
+        config.project, config.mail_worker, config.logging.level
+    );
 
     let component_name = match env::var("COMPONENT") {
         Ok(comp) => comp,
@@ -192,27 +740,183 @@ pub async fn main() -> std::io::Result<()> {
     app_host.push(':');
     app_host.push_str(app_port.as_str());
 
-    println!(
-        "Email App '{}': launching at {} ...",
-        component_name, app_host
+    info!(
+        "Email App '{}': launching at {}://{} ...",
+        component_name,
+        if config.tls.enabled { "https" } else { "http" },
+        app_host
     );
 
+    if !config.auth.enabled {
+        warn!(
+            "Email App '{}': Authentication is disabled. Anyone can send Emails!",
+            component_name
+        );
+    }
+
+    //Create 1 Metrics Registry shared by all HTTP Workers and Email Workers
+    let metrics = Metrics::new(&config.component);
+
+    //Create 1 Worker Monitor that lives as long as the Process
+    let monitor = WorkerMonitor::new(config.mail_worker as usize).start();
+
     //Clone the SMTP Config for the Email Worker
     let smtp_config = config.smtp.clone();
+    let sender_metrics = metrics.clone();
+    let sender_monitor = monitor.clone();
+    let next_worker = AtomicUsize::new(0);
+
+    //The Application does not start when the configured DKIM Key cannot be loaded
+    if smtp_config.dkim.enabled {
+        DkimSigner::from_config(&smtp_config.dkim)?;
+    }
 
     //Create 2 Email Sender Instances
+    //A panicked Email Sender is replaced by the Factory on the same Thread
     let sender = SyncArbiter::start(config.mail_worker as usize, move || {
-        EmailSender::from_config(&smtp_config)
+        let mut sender = EmailSender::from_config(&smtp_config);
+
+        sender.set_metrics(&sender_metrics);
+        sender.set_monitor(worker_id(&next_worker), sender_monitor.clone());
+        sender
     });
     //Create 1 Email Link Object
-    let link = EmailLink::new(sender);
+    let mut link = EmailLink::new(sender);
+    //Load the Outbox with the Emails that were left at the last Shutdown
+    let outbox = Outbox::from_config(&config.outbox, &config.data_directory)?;
+    //Create 1 Circuit Breaker around the SMTP Relay
+    let mut circuit = CircuitBreaker::from_config(&config.circuit);
+
+    circuit.set_metrics(&metrics);
+    link.set_metrics(&metrics);
+    link.set_circuit(&circuit);
+    link.set_mailbox(&config.mailbox);
+    link.set_outbox(&outbox);
+
+    if config.smtp.send_timeout > 0 {
+        link.set_send_timeout(Duration::from_secs(config.smtp.send_timeout));
+    }
+
+    //Create 1 Rate Limiter shared by all HTTP Workers
+    let mut limiter = RateLimiter::from_config(&config.rate_limit);
+
+    limiter.set_metrics(&metrics);
+
+    //Create 1 Routing Tree shared by all HTTP Workers
+    let mut router = Router::from_config(&config.routing);
+
+    router.set_validation(&config.validation);
+
+    //Load the Silences shared by all HTTP Workers
+    let silences = SilenceStore::from_directory(&config.data_directory)?;
+    //Save the Records of the suppressed Emails periodically
+    actix_rt::spawn(silences.clone().watch(silence::SAVE_INTERVAL));
+
+    //Load the Acknowledgements for the Alert Links
+    let links = AlertLinks::from_config(&config.links, &config.data_directory)?;
+    //Load the Email Threads shared by all HTTP Workers
+    let threads = ThreadStore::from_config(
+        &config.threading,
+        &config.smtp.email_address,
+        &config.data_directory,
+    )?;
+
+    //Save the changed Threads periodically
+    if config.threading.enabled {
+        actix_rt::spawn(threads.clone().watch(threading::SAVE_INTERVAL));
+    }
 
-    HttpServer::new(move || {
+    //Create 1 Deduplication Cache shared by all HTTP Workers
+    let dedup = DedupCache::from_config(&config.dedup);
+
+    //Send the Summaries of the merged Duplicates when their Window closes
+    if config.dedup.enabled && config.dedup.action == "merge" {
+        actix_rt::spawn(dedup.clone().watch(link.clone()));
+    }
+
+    //Create 1 Health Check Cache shared by all HTTP Workers
+    let health = HealthChecks::from_config(&config.health);
+    //Send the Emails that were left at the last Shutdown again
+    actix_rt::spawn(outbox.clone().resend(link.clone()));
+
+    //Send the Emails that were kept while the SMTP Relay was failing or the Mailbox was full
+    if config.outbox.enabled {
+        actix_rt::spawn(outbox.clone().watch(
+            link.clone(),
+            Duration::from_secs(config.outbox.interval.max(1)),
+        ));
+    }
+
+    //Create 1 Digest Actor if Digests are enabled
+    let digest = if config.digest.enabled {
+        let mut digest =
+            DigestActor::from_config(&config.digest, &config.smtp.email_address, link.clone());
+
+        digest.set_threads(&threads);
+
+        Some(digest.start())
+    } else {
+        None
+    };
+
+    //Create 1 Escalation Actor if Escalations are enabled
+    let escalation = if config.escalation.enabled {
+        let mut escalation = EscalationActor::from_config(
+            &config.escalation,
+            &config.data_directory,
+            link.clone(),
+            links.clone(),
+        )?;
+
+        escalation.set_silences(&silences);
+
+        Some(escalation.start())
+    } else {
+        None
+    };
+
+    //Clone the TLS Config for the HTTPS Listener
+    let tls_config = config.tls.clone();
+    //Keep the Email Link to await the Emails at the Shutdown
+    let shutdown_config = config.shutdown.clone();
+    let shutdown_link = link.clone();
+    let shutdown_digest = digest.clone();
+    let shutdown_silences = silences.clone();
+    let shutdown_threads = threads.clone();
+
+    let server = HttpServer::new(move || {
         let app_config = web::Data::new(config.clone());
         let link_data = web::Data::new(link.clone());
+        let limiter_data = web::Data::new(limiter.clone());
+        let dedup_data = web::Data::new(dedup.clone());
+        let router_data = web::Data::new(router.clone());
+        let silence_data = web::Data::new(silences.clone());
+        let links_data = web::Data::new(links.clone());
+        let threads_data = web::Data::new(threads.clone());
+        let metrics_data = web::Data::new(metrics.clone());
+        let health_data = web::Data::new(health.clone());
+        let monitor_data = web::Data::new(monitor.clone());
+
+        let mut app = App::new();
+
+        if let Some(digest) = &digest {
+            app = app.app_data(web::Data::new(digest.clone()));
+        }
 
-        App::new()
-            .app_data(link_data)
+        if let Some(escalation) = &escalation {
+            app = app.app_data(web::Data::new(escalation.clone()));
+        }
+
+        app.app_data(link_data)
+            .app_data(limiter_data)
+            .app_data(dedup_data)
+            .app_data(router_data)
+            .app_data(silence_data)
+            .app_data(links_data)
+            .app_data(threads_data)
+            .app_data(metrics_data)
+            .app_data(health_data)
+            .app_data(monitor_data)
             .app_data(web::JsonConfig::default().limit(MAX_SIZE)) // <- limit size of the payload (global configuration)
             .service(
                 web::resource(app_config.web_root.as_str())
@@ -220,8 +924,55 @@ pub async fn main() -> std::io::Result<()> {
             )
             .service(
                 web::resource(app_config.web_root.as_str().to_owned() + "send")
+                    .wrap(Authentication::new(&app_config.auth))
+                    .wrap(AccessFilter::new(&app_config.access))
                     .route(web::post().to(send_email)),
             )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "admin/limits")
+                    .wrap(Authentication::new(&app_config.auth))
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::get().to(dispatch_limits_request)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "digest/flush")
+                    .wrap(Authentication::new(&app_config.auth))
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::post().to(dispatch_digest_flush)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "route/test")
+                    .wrap(Authentication::new(&app_config.auth))
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::post().to(dispatch_route_test)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "silences")
+                    .wrap(Authentication::new(&app_config.auth))
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::get().to(dispatch_silence_list))
+                    .route(web::post().to(dispatch_silence_create)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "silences/{id}")
+                    .wrap(Authentication::new(&app_config.auth))
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::delete().to(dispatch_silence_delete)),
+            )
+            .service(
+                // The signed Token authorises the Link
+                web::resource(app_config.web_root.as_str().to_owned() + "alert/ack")
+                    .route(web::get().to(dispatch_alert_ack)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "alert/silence")
+                    .route(web::get().to(dispatch_alert_silence)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "metrics")
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::get().to(dispatch_metrics)),
+            )
             .service(
                 web::resource(app_config.web_root.as_str().to_owned() + "mjsonrust")
                     .route(web::post().to(index_mjsonrust)),
@@ -230,14 +981,60 @@ pub async fn main() -> std::io::Result<()> {
                 web::resource(app_config.web_root.as_str().to_owned() + "ping")
                     .route(web::get().to(dispatch_ping_request)),
             )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "status")
+                    .wrap(AccessFilter::new(&app_config.access))
+                    .route(web::get().to(dispatch_worker_status)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "health/live")
+                    .route(web::get().to(dispatch_health_live)),
+            )
+            .service(
+                web::resource(app_config.web_root.as_str().to_owned() + "health/ready")
+                    .route(web::get().to(dispatch_health_ready)),
+            )
             .app_data(app_config)
-            .wrap(Logger::default())
-    })
-    .bind(app_host)?
-    .run()
-    .await?;
+            .wrap(RequestMetrics::new(&metrics))
+            .wrap(Correlation)
+            // The Access Log is written after the Request Scope and links it by the Response Header
+            .wrap(Logger::new(
+                "%a \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T request_id=%{X-Request-ID}o",
+            ))
+    });
+
+    let server = if tls_config.enabled {
+        let resolver = Arc::new(CertificateResolver::from_config(&tls_config)?);
+
+        tls::reload_on_hangup(resolver.clone())?;
+
+        server.bind_rustls(app_host, tls::server_config(&tls_config, resolver)?)?
+    } else {
+        server.bind(app_host)?
+    };
+
+    //The Signals are handled by the Shutdown Sequence
+    let server = server
+        .disable_signals()
+        .shutdown_timeout(shutdown_config.drain_timeout)
+        .run();
+    let clock = shutdown::stop_on_signal(server.clone())?;
+
+    server.await?;
+
+    //The collected Digests are sent and drained with the other Emails
+    if let Some(digest) = shutdown_digest {
+        match digest.send(DigestFlush).await {
+            Ok(count) => info!("Shutdown: {} Digests dispatched.", count),
+            Err(e) => error!("Shutdown: Digests could not be flushed: '{:?}'", e),
+        }
+    }
+
+    shutdown::drain(&shutdown_link, &outbox, &shutdown_config, clock.started()).await;
+    shutdown_silences.flush();
+    shutdown_threads.flush();
 
-    println!("Email App '{}': finished.", component_name);
+    info!("Email App '{}': finished.", component_name);
 
     Ok(())
 }
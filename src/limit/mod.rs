@@ -0,0 +1,416 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Rate Limiting
+
+* This Module defines the Token Buckets that throttle the outgoing Emails
+* globally, per Recipient and per Caller
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpResponse, ResponseError};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{BucketConfig, RateLimitConfig};
+use crate::metrics::Metrics;
+use crate::ResponseData;
+
+/// At most this Count of Buckets is kept per Map
+/// New Recipients or Callers are refused while all Buckets are in use
+pub const MAX_BUCKETS: usize = 1024;
+/// The Maps are checked for refilled Buckets at most once per Minute
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+//==============================================================================
+// Structure LimitError Declaration
+
+/// Structure for exceeded Rate Limits
+#[derive(Debug)]
+pub struct LimitError {
+    pub report: String,
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rate Limit exceeded: '{}'", self.report)
+    }
+}
+
+impl ResponseError for LimitError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        // Retry-After is given in whole seconds
+        let retry_after = self.retry_after.as_secs() + 1;
+
+        HttpResponse::TooManyRequests()
+            .header(header::RETRY_AFTER, retry_after.to_string())
+            .json(ResponseData {
+                title: String::from("Too Many Requests"),
+                statuscode: 429,
+                page: String::from("Send"),
+                description: self.to_string(),
+            })
+    }
+}
+
+//==============================================================================
+// Structure TokenBucket Declaration
+
+/// Structure for a single Token Bucket
+/// The Bucket holds up to `capacity` Tokens and refills `per_minute` Tokens each Minute
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Structure for the reported State of a Token Bucket
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BucketStatus {
+    pub capacity: u32,
+    pub per_minute: u32,
+    pub tokens: f64,
+}
+
+/// Structure for the reported State of all Token Buckets
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LimitStatus {
+    pub enabled: bool,
+    pub mode: String,
+    pub global: Option<BucketStatus>,
+    pub recipients: BTreeMap<String, BucketStatus>,
+    pub callers: BTreeMap<String, BucketStatus>,
+}
+
+//==============================================================================
+// Structure TokenBucket Implementation
+
+impl TokenBucket {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &BucketConfig) -> Self {
+        Self {
+            capacity: config.capacity as f64,
+            rate: config.per_minute as f64 / 60.0,
+            tokens: config.capacity as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Returns the Time to wait until the next Token is available
+    fn wait_time(&mut self, now: Instant) -> Option<Duration> {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            None
+        } else if self.rate > 0.0 {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        } else {
+            Some(Duration::from_secs(60))
+        }
+    }
+
+    fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    fn is_full(&self) -> bool {
+        self.tokens >= self.capacity
+    }
+
+    /// Returns the Time until the Bucket is refilled to its Capacity
+    fn full_time(&self) -> Duration {
+        if self.rate > 0.0 {
+            Duration::from_secs_f64((self.capacity - self.tokens).max(0.0) / self.rate)
+        } else {
+            Duration::from_secs(60)
+        }
+    }
+
+    fn status(&mut self, now: Instant) -> BucketStatus {
+        self.refill(now);
+
+        BucketStatus {
+            capacity: self.capacity as u32,
+            per_minute: (self.rate * 60.0).round() as u32,
+            tokens: self.tokens,
+        }
+    }
+}
+
+//==============================================================================
+// Structure RateLimiter Declaration
+
+/// Structure for the shared Rate Limiter of all HTTP Workers
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Arc<Mutex<Buckets>>,
+    metrics: Option<Metrics>,
+}
+
+struct Buckets {
+    global: Option<TokenBucket>,
+    recipients: HashMap<String, TokenBucket>,
+    callers: HashMap<String, TokenBucket>,
+    pruned: Instant,
+}
+
+//==============================================================================
+// Structure RateLimiter Implementation
+
+impl RateLimiter {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        let global = if config.global.capacity > 0 {
+            Some(TokenBucket::from_config(&config.global))
+        } else {
+            None
+        };
+
+        Self {
+            config: config.clone(),
+            buckets: Arc::new(Mutex::new(Buckets {
+                global,
+                recipients: HashMap::new(),
+                callers: HashMap::new(),
+                pruned: Instant::now(),
+            })),
+            metrics: None,
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Reports the Queue Length and the Retries to the Metrics
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.metrics = Some(metrics.clone());
+    }
+
+    /// Takes a Token from every Bucket that applies to the Email
+    /// Tokens are only taken when all Buckets have a Token available
+    /// otherwise the longest Time to wait is returned.
+    /// The `recipients` must be the Addresses the Email is delivered to
+    pub fn try_acquire(&self, recipients: &[String], caller: &str) -> Result<(), LimitError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let mut buckets = match self.buckets.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+        let now = Instant::now();
+        // Each Address is charged once however often it is listed
+        let recipients: BTreeSet<String> = recipients
+            .iter()
+            .map(|recipient| recipient.to_lowercase())
+            .collect();
+
+        if buckets.recipients.len() >= MAX_BUCKETS
+            || buckets.callers.len() >= MAX_BUCKETS
+            || now.saturating_duration_since(buckets.pruned) >= PRUNE_INTERVAL
+        {
+            prune_buckets(&mut buckets.recipients, now);
+            prune_buckets(&mut buckets.callers, now);
+            buckets.pruned = now;
+        }
+
+        let mut wait: Option<(Duration, String)> = None;
+        let mut exceeded = |bucket_wait: Option<Duration>, name: String| {
+            if let Some(w) = bucket_wait {
+                if wait
+                    .as_ref()
+                    .map(|(current, _)| w > *current)
+                    .unwrap_or(true)
+                {
+                    wait = Some((w, name));
+                }
+            }
+        };
+
+        if let Some(global) = buckets.global.as_mut() {
+            exceeded(global.wait_time(now), String::from("global"));
+        }
+
+        if self.config.per_recipient.capacity > 0 {
+            for recipient in &recipients {
+                let bucket_wait = match find_bucket(
+                    &mut buckets.recipients,
+                    recipient,
+                    &self.config.per_recipient,
+                ) {
+                    Ok(bucket) => bucket.wait_time(now),
+                    Err(full) => Some(full),
+                };
+
+                exceeded(bucket_wait, format!("recipient '{}'", recipient));
+            }
+        }
+
+        if self.config.per_caller.capacity > 0 {
+            let bucket_wait =
+                match find_bucket(&mut buckets.callers, caller, &self.config.per_caller) {
+                    Ok(bucket) => bucket.wait_time(now),
+                    Err(full) => Some(full),
+                };
+
+            exceeded(bucket_wait, format!("caller '{}'", caller));
+        }
+
+        if let Some((retry_after, name)) = wait {
+            return Err(LimitError {
+                report: format!("Limit for {} is exhausted", name),
+                retry_after,
+            });
+        }
+
+        if let Some(global) = buckets.global.as_mut() {
+            global.take();
+        }
+
+        for recipient in &recipients {
+            if let Some(bucket) = buckets.recipients.get_mut(recipient) {
+                bucket.take();
+            }
+        }
+
+        if let Some(bucket) = buckets.callers.get_mut(caller) {
+            bucket.take();
+        }
+
+        Ok(())
+    }
+
+    /// Takes the Tokens for the Email and waits for them in `queue` Mode
+    /// as long as the Wait does not exceed `max_queue_wait` Seconds
+    pub async fn acquire(&self, recipients: &[String], caller: &str) -> Result<(), LimitError> {
+        let max_wait = Duration::from_secs(self.config.max_queue_wait);
+        let mut waited = Duration::from_secs(0);
+        // The Email leaves the Queue when it got its Tokens, gave up or was cancelled
+        let mut queued = None;
+
+        loop {
+            match self.try_acquire(recipients, caller) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    if self.config.mode != "queue" || waited + e.retry_after > max_wait {
+                        return Err(e);
+                    }
+
+                    info!("Rate Limit: {}; queued for {:?}", e, e.retry_after);
+
+                    // Each Wait is followed by a Retry
+                    if let Some(metrics) = &self.metrics {
+                        if queued.is_none() {
+                            queued = Some(metrics.enter_queue());
+                        }
+
+                        metrics.observe_retry();
+                    }
+
+                    waited += e.retry_after;
+                    actix_rt::time::delay_for(e.retry_after).await;
+                }
+            }
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn status(&self) -> LimitStatus {
+        let mut buckets = match self.buckets.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+        let now = Instant::now();
+
+        LimitStatus {
+            enabled: self.config.enabled,
+            mode: self.config.mode.clone(),
+            global: buckets.global.as_mut().map(|bucket| bucket.status(now)),
+            recipients: buckets
+                .recipients
+                .iter_mut()
+                .map(|(name, bucket)| (name.clone(), bucket.status(now)))
+                .collect(),
+            callers: buckets
+                .callers
+                .iter_mut()
+                .map(|(name, bucket)| (name.clone(), bucket.status(now)))
+                .collect(),
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Removes refilled Buckets which carry no State anymore
+/// Buckets which still miss Tokens are kept so that their Limit is not reset
+fn prune_buckets(buckets: &mut HashMap<String, TokenBucket>, now: Instant) {
+    buckets.retain(|_, bucket| {
+        bucket.refill(now);
+        !bucket.is_full()
+    });
+}
+
+/// Looks up the Bucket of the Name and adds it when the Map has Room
+/// When all Buckets are in use the Time until the first of them is refilled is returned
+fn find_bucket<'a>(
+    buckets: &'a mut HashMap<String, TokenBucket>,
+    name: &str,
+    config: &BucketConfig,
+) -> Result<&'a mut TokenBucket, Duration> {
+    if !buckets.contains_key(name) && buckets.len() >= MAX_BUCKETS {
+        return Err(buckets
+            .values()
+            .map(TokenBucket::full_time)
+            .min()
+            .unwrap_or_else(|| Duration::from_secs(60)));
+    }
+
+    Ok(buckets
+        .entry(name.to_owned())
+        .or_insert_with(|| TokenBucket::from_config(config)))
+}
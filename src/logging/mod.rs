@@ -0,0 +1,177 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Logging
+
+* This Module defines the Logger behind the "log" Facade which writes
+* one JSON Object per Line to the Standard Error
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "log" must be installed
+* - The Rust Crate "env_logger" must be installed
+* - The Rust Crate "serde-json" must be installed
+*/
+
+use std::cell::RefCell;
+use std::env;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use env_logger::filter::{Builder, Filter};
+use log::{Log, Metadata, Record};
+use serde_json::json;
+
+use crate::config::LogConfig;
+
+/// The Logger is installed once and reconfigured when the Configuration is loaded
+static LOGGER: JsonLogger = JsonLogger {
+    settings: RwLock::new(None),
+};
+
+/// Request Bodies and Email Contents are only logged when this is enabled
+static LOG_BODIES: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Request ID of the Request which is processed on this Thread right now
+    static REQUEST_ID: RefCell<Option<String>> = RefCell::new(None);
+}
+
+//==============================================================================
+// Structure JsonLogger Declaration
+
+/// Structure for the Logger of the whole Application
+pub struct JsonLogger {
+    settings: RwLock<Option<LogSettings>>,
+}
+
+/// Structure for the active Settings of the Logger
+struct LogSettings {
+    filter: Filter,
+    component: String,
+    json: bool,
+}
+
+//==============================================================================
+// Structure JsonLogger Implementation
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match self.settings.read() {
+            Ok(settings) => settings
+                .as_ref()
+                .map(|s| s.filter.enabled(metadata))
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        let settings = match self.settings.read() {
+            Ok(settings) => settings,
+            Err(_) => return,
+        };
+        let settings = match settings.as_ref() {
+            Some(settings) if settings.filter.matches(record) => settings,
+            _ => return,
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as f64 / 1000.0)
+            .unwrap_or(0.0);
+        let request_id = request_id();
+        let line = if settings.json {
+            let mut line = json!({
+                "ts": timestamp,
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "component": settings.component,
+                "message": record.args().to_string(),
+            });
+
+            if let Some(request_id) = request_id {
+                line["request_id"] = json!(request_id);
+            }
+
+            line.to_string()
+        } else {
+            format!(
+                "{:.3} {:<5} {}: {}{}",
+                timestamp,
+                record.level(),
+                record.target(),
+                request_id
+                    .map(|id| format!("[{}] ", id))
+                    .unwrap_or_default(),
+                record.args()
+            )
+        };
+
+        let _ = writeln!(std::io::stderr().lock(), "{}", line);
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Installs the Logger with the default Settings
+/// so that the Loading of the Configuration is already logged
+pub fn init() {
+    configure(&LogConfig::new(), "unknown");
+}
+
+/// Applies the Log Level and the Format of the Configuration
+/// The `RUST_LOG` Environment Variable overrides the configured Level
+pub fn configure(config: &LogConfig, component: &str) {
+    let mut builder = Builder::new();
+
+    builder.parse(&config.level);
+
+    if let Ok(filters) = env::var("RUST_LOG") {
+        builder.parse(&filters);
+    }
+
+    let filter = builder.build();
+
+    log::set_max_level(filter.filter());
+    LOG_BODIES.store(config.log_bodies, Ordering::Relaxed);
+
+    if let Ok(mut settings) = LOGGER.settings.write() {
+        *settings = Some(LogSettings {
+            filter,
+            component: component.to_owned(),
+            json: config.format != "text",
+        });
+    }
+
+    // The Logger can only be installed once
+    let _ = log::set_logger(&LOGGER);
+}
+
+/// Tells whether Request Bodies and Email Contents may be logged
+pub fn log_bodies() -> bool {
+    LOG_BODIES.load(Ordering::Relaxed)
+}
+
+/// Runs the Function with the Request ID that is added to each Log Line
+/// The Request ID of an outer Scope is restored afterwards
+pub fn with_request_id<T>(request_id: Option<&str>, f: impl FnOnce() -> T) -> T {
+    let outer = REQUEST_ID.with(|id| id.replace(request_id.map(str::to_owned)));
+    let result = f();
+
+    REQUEST_ID.with(|id| *id.borrow_mut() = outer);
+
+    result
+}
+
+/// Returns the Request ID of the current Scope
+pub fn request_id() -> Option<String> {
+    REQUEST_ID.with(|id| id.borrow().clone())
+}
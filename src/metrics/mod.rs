@@ -0,0 +1,407 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Metrics
+
+* This Module defines the Counters of the Mail Pipeline and their Export
+* in the Prometheus Text Format
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::{ok, FutureExt, LocalBoxFuture, Ready};
+
+/// Upper Bounds of the SMTP Latency Buckets in Seconds
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 15.0, 30.0];
+
+//==============================================================================
+// Structure Metrics Declaration
+
+/// Structure for the shared Metrics of all HTTP Workers and Email Workers
+#[derive(Clone)]
+pub struct Metrics {
+    component: String,
+    state: Arc<Mutex<MetricsState>>,
+}
+
+/// Structure for the collected Values
+#[derive(Debug, Default)]
+struct MetricsState {
+    /// Requests by Route and Status Code
+    requests: BTreeMap<(String, u16), u64>,
+    /// Sent and failed Emails by Outcome or Error Class
+    sends: BTreeMap<String, u64>,
+    smtp_latency: Histogram,
+    /// Emails sent to the `SyncArbiter` which are not answered yet
+    mailbox_depth: i64,
+    /// Emails waiting for the Rate Limit in `queue` Mode
+    queue_length: i64,
+    /// Attempts to take the Rate Limit Tokens again after a Wait
+    retries: u64,
+    /// Email Workers which panicked and were replaced
+    restarts: u64,
+    /// State of the Circuit Breaker: 0 closed, 1 half-open, 2 open
+    circuit_state: i64,
+    /// Times the Circuit Breaker opened
+    circuit_opened: u64,
+}
+
+/// Structure for a cumulative Prometheus Histogram
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+/// The Gauges which count the Emails that are waiting
+#[derive(Debug, Clone, Copy)]
+enum Gauge {
+    MailboxDepth,
+    QueueLength,
+}
+
+/// Structure that decrements its Gauge when the waiting Email leaves
+/// This also covers Requests which are cancelled while they wait
+pub struct GaugeGuard {
+    metrics: Metrics,
+    gauge: Gauge,
+}
+
+//==============================================================================
+// Structure Metrics Implementation
+
+impl Metrics {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(component: &str) -> Self {
+        Self {
+            component: component.to_owned(),
+            state: Arc::new(Mutex::new(MetricsState::default())),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Counts a finished HTTP Request by its Route Pattern and Status Code
+    pub fn observe_request(&self, route: &str, status: u16) {
+        *self
+            .lock()
+            .requests
+            .entry((route.to_owned(), status))
+            .or_insert(0) += 1;
+    }
+
+    /// Counts a finished Email by its Outcome like `sent` or the Error Class
+    pub fn observe_send(&self, outcome: &str) {
+        *self.lock().sends.entry(outcome.to_owned()).or_insert(0) += 1;
+    }
+
+    /// Records the Duration of an SMTP Transaction
+    pub fn observe_smtp_latency(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let mut state = self.lock();
+
+        for (bucket, bound) in state.smtp_latency.buckets.iter_mut().zip(&LATENCY_BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        state.smtp_latency.sum += seconds;
+        state.smtp_latency.count += 1;
+    }
+
+    /// Counts an Email in the Mailbox of the Email Workers until the Guard is dropped
+    pub fn enter_mailbox(&self) -> GaugeGuard {
+        self.lock().mailbox_depth += 1;
+
+        GaugeGuard {
+            metrics: self.clone(),
+            gauge: Gauge::MailboxDepth,
+        }
+    }
+
+    /// Counts an Email in the Rate Limit Queue until the Guard is dropped
+    pub fn enter_queue(&self) -> GaugeGuard {
+        self.lock().queue_length += 1;
+
+        GaugeGuard {
+            metrics: self.clone(),
+            gauge: Gauge::QueueLength,
+        }
+    }
+
+    /// Counts another Attempt to take the Rate Limit Tokens after a Wait
+    pub fn observe_retry(&self) {
+        self.lock().retries += 1;
+    }
+
+    pub fn observe_restart(&self) {
+        self.lock().restarts += 1;
+    }
+
+    pub fn set_circuit_state(&self, state: i64) {
+        self.lock().circuit_state = state;
+    }
+
+    pub fn observe_circuit_open(&self) {
+        self.lock().circuit_opened += 1;
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Renders all Metrics in the Prometheus Text Exposition Format
+    pub fn render(&self) -> String {
+        let state = self.lock();
+        let component = escape_label(&self.component);
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP alerting_email_http_requests_total HTTP Requests by Route and Status."
+        );
+        let _ = writeln!(out, "# TYPE alerting_email_http_requests_total counter");
+
+        for ((route, status), count) in &state.requests {
+            let _ = writeln!(
+                out,
+                "alerting_email_http_requests_total{{component=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                component,
+                escape_label(route),
+                status,
+                count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP alerting_email_sends_total Emails by Outcome or Error Class."
+        );
+        let _ = writeln!(out, "# TYPE alerting_email_sends_total counter");
+
+        for (outcome, count) in &state.sends {
+            let _ = writeln!(
+                out,
+                "alerting_email_sends_total{{component=\"{}\",outcome=\"{}\"}} {}",
+                component,
+                escape_label(outcome),
+                count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP alerting_email_smtp_duration_seconds Duration of the SMTP Transactions."
+        );
+        let _ = writeln!(out, "# TYPE alerting_email_smtp_duration_seconds histogram");
+
+        for (count, bound) in state.smtp_latency.buckets.iter().zip(&LATENCY_BUCKETS) {
+            let _ = writeln!(
+                out,
+                "alerting_email_smtp_duration_seconds_bucket{{component=\"{}\",le=\"{}\"}} {}",
+                component, bound, count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "alerting_email_smtp_duration_seconds_bucket{{component=\"{}\",le=\"+Inf\"}} {}",
+            component, state.smtp_latency.count
+        );
+        let _ = writeln!(
+            out,
+            "alerting_email_smtp_duration_seconds_sum{{component=\"{}\"}} {}",
+            component, state.smtp_latency.sum
+        );
+        let _ = writeln!(
+            out,
+            "alerting_email_smtp_duration_seconds_count{{component=\"{}\"}} {}",
+            component, state.smtp_latency.count
+        );
+
+        for (name, kind, help, value) in [
+            (
+                "alerting_email_worker_mailbox_depth",
+                "gauge",
+                "Emails waiting for or processed by the Email Workers.",
+                state.mailbox_depth.to_string(),
+            ),
+            (
+                "alerting_email_queue_length",
+                "gauge",
+                "Emails waiting for the Rate Limit.",
+                state.queue_length.to_string(),
+            ),
+            (
+                "alerting_email_retries_total",
+                "counter",
+                "Retries to take the Rate Limit Tokens.",
+                state.retries.to_string(),
+            ),
+            (
+                "alerting_email_worker_restarts_total",
+                "counter",
+                "Email Workers restarted after a Panic.",
+                state.restarts.to_string(),
+            ),
+            (
+                "alerting_email_circuit_state",
+                "gauge",
+                "State of the SMTP Circuit Breaker: 0 closed, 1 half-open, 2 open.",
+                state.circuit_state.to_string(),
+            ),
+            (
+                "alerting_email_circuit_opened_total",
+                "counter",
+                "Times the SMTP Circuit Breaker opened.",
+                state.circuit_opened.to_string(),
+            ),
+        ]
+        .iter()
+        {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{}{{component=\"{}\"}} {}", name, component, value);
+        }
+
+        out
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MetricsState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+//==============================================================================
+// Structure GaugeGuard Implementation
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        let mut state = self.metrics.lock();
+
+        match self.gauge {
+            Gauge::MailboxDepth => state.mailbox_depth -= 1,
+            Gauge::QueueLength => state.queue_length -= 1,
+        }
+    }
+}
+
+//==============================================================================
+// Structure RequestMetrics Declaration
+
+/// Middleware to count the HTTP Requests by their Route Pattern and Status Code
+pub struct RequestMetrics {
+    metrics: Rc<Metrics>,
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: S,
+    metrics: Rc<Metrics>,
+}
+
+//==============================================================================
+// Structure RequestMetrics Implementation
+
+impl RequestMetrics {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(metrics: &Metrics) -> Self {
+        Self {
+            metrics: Rc::new(metrics.clone()),
+        }
+    }
+}
+
+impl<S, B> Transform<S> for RequestMetrics
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestMetricsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestMetricsMiddleware {
+            service,
+            metrics: self.metrics.clone(),
+        })
+    }
+}
+
+impl<S, B> Service for RequestMetricsMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        // The Pattern keeps the Label Values bounded for Paths like `silences/{id}`
+        let route = req
+            .request()
+            .match_pattern()
+            .unwrap_or_else(|| String::from("unmatched"));
+        let metrics = self.metrics.clone();
+
+        self.service
+            .call(req)
+            .map(move |res| {
+                let status = match &res {
+                    Ok(rs) => rs.status().as_u16(),
+                    Err(e) => e.as_response_error().status_code().as_u16(),
+                };
+
+                metrics.observe_request(&route, status);
+
+                res
+            })
+            .boxed_local()
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Escapes a Label Value for the Prometheus Text Format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
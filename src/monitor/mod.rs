@@ -0,0 +1,233 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Worker Monitor
+
+* This Module defines the Actor that lives as long as the Process
+* and tracks the State of each Email Sending Worker
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use actix::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::email::QueueStatus;
+
+thread_local! {
+    /// Number of the Email Worker which runs on this Thread
+    static WORKER_ID: Cell<Option<usize>> = Cell::new(None);
+}
+
+//==============================================================================
+// Structure WorkerMessage Declaration
+
+/// Events reported by the Email Workers
+#[derive(Debug, Clone)]
+pub enum WorkerEvent {
+    Started,
+    Busy,
+    Succeeded,
+    Failed(String),
+    /// The Worker panicked and is replaced by a new one
+    Panicked(String),
+    Stopped,
+}
+
+/// Message of an Email Worker about its State
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct WorkerReport {
+    pub worker: usize,
+    pub event: WorkerEvent,
+}
+
+/// Message to get the State of all Email Workers
+#[derive(Debug, Message)]
+#[rtype(result = "MonitorStatus")]
+pub struct MonitorQuery;
+
+//==============================================================================
+// Structure WorkerStatus Declaration
+
+/// Structure for the tracked State of one Email Worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerStatus {
+    pub worker: usize,
+    /// `starting`, `idle`, `busy`, `restarting` or `stopped`
+    pub state: String,
+    pub messages_handled: u64,
+    pub failures: u64,
+    /// Unix Time of the last sent Email
+    pub last_success: Option<u64>,
+    /// Unix Time of the last failed Email
+    pub last_failure: Option<u64>,
+    pub last_error: Option<String>,
+    /// Times the Worker panicked and was restarted
+    pub restarts: u64,
+}
+
+/// Structure for the reported State of all Email Workers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorStatus {
+    pub uptime: u64,
+    pub workers: Vec<WorkerStatus>,
+    /// Depth of the Email Queue which is added by the Status Endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueueStatus>,
+}
+
+//==============================================================================
+// Structure WorkerMonitor Declaration
+
+/// Structure for the Supervision of the Email Workers
+pub struct WorkerMonitor {
+    started: Instant,
+    workers: BTreeMap<usize, WorkerStatus>,
+}
+
+//==============================================================================
+// Structure WorkerStatus Implementation
+
+impl WorkerStatus {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new(worker: usize) -> Self {
+        Self {
+            worker,
+            state: String::from("starting"),
+            messages_handled: 0,
+            failures: 0,
+            last_success: None,
+            last_failure: None,
+            last_error: None,
+            restarts: 0,
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    fn apply(&mut self, event: WorkerEvent) {
+        match event {
+            WorkerEvent::Started => self.state = String::from("idle"),
+            WorkerEvent::Busy => self.state = String::from("busy"),
+            WorkerEvent::Succeeded => {
+                self.state = String::from("idle");
+                self.messages_handled += 1;
+                self.last_success = Some(unix_time());
+            }
+            WorkerEvent::Failed(report) => {
+                self.state = String::from("idle");
+                self.messages_handled += 1;
+                self.failures += 1;
+                self.last_failure = Some(unix_time());
+                self.last_error = Some(report);
+            }
+            WorkerEvent::Panicked(report) => {
+                self.state = String::from("restarting");
+                self.messages_handled += 1;
+                self.failures += 1;
+                self.restarts += 1;
+                self.last_failure = Some(unix_time());
+                self.last_error = Some(report);
+            }
+            WorkerEvent::Stopped => self.state = String::from("stopped"),
+        }
+    }
+}
+
+//==============================================================================
+// Structure WorkerMonitor Implementation
+
+impl WorkerMonitor {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Creates the Monitor for the configured Count of Email Workers
+    /// Workers which have not reported yet are shown as `starting`
+    pub fn new(workers: usize) -> Self {
+        Self {
+            started: Instant::now(),
+            workers: (0..workers)
+                .map(|worker| (worker, WorkerStatus::new(worker)))
+                .collect(),
+        }
+    }
+}
+
+impl Actor for WorkerMonitor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        info!(
+            "Worker Monitor is alive; watching {} Email Workers",
+            self.workers.len()
+        );
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        info!("Worker Monitor is stopped");
+    }
+}
+
+/// Define handler for `WorkerReport` Message
+impl Handler<WorkerReport> for WorkerMonitor {
+    type Result = ();
+
+    fn handle(&mut self, msg: WorkerReport, _ctx: &mut Self::Context) -> Self::Result {
+        self.workers
+            .entry(msg.worker)
+            .or_insert_with(|| WorkerStatus::new(msg.worker))
+            .apply(msg.event);
+    }
+}
+
+/// Define handler for `MonitorQuery` Message
+impl Handler<MonitorQuery> for WorkerMonitor {
+    type Result = MessageResult<MonitorQuery>;
+
+    fn handle(&mut self, _msg: MonitorQuery, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(MonitorStatus {
+            uptime: self.started.elapsed().as_secs(),
+            workers: self.workers.values().cloned().collect(),
+            queue: None,
+        })
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Assigns the Email Worker of this Thread its Number
+/// A Worker which is restarted on the same Thread keeps the Number of its Predecessor
+pub fn worker_id(next: &AtomicUsize) -> usize {
+    WORKER_ID.with(|id| match id.get() {
+        Some(worker) => worker,
+        None => {
+            let worker = next.fetch_add(1, Ordering::SeqCst);
+
+            id.set(Some(worker));
+            worker
+        }
+    })
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,301 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Outbox
+
+* This Module defines the persistent Outbox that keeps the Emails which could not
+* be sent before the Shutdown or while the SMTP Relay was failing and sends them again
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "serde-json" must be installed
+*/
+
+use std::io::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{OutboxConfig, SenderConfig};
+use crate::email::{EmailData, EmailErrorKind, EmailLink};
+use crate::store;
+
+const OUTBOX_FILE: &str = "outbox.json";
+
+//==============================================================================
+// Structure OutboxEntry Declaration
+
+/// Structure for an Email in the Outbox
+/// The Fields which are assigned after the Request was accepted are kept as well
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub email: EmailData,
+    pub job: String,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub route: Option<String>,
+    #[serde(default)]
+    pub deliver_to: Vec<String>,
+    #[serde(default)]
+    pub sender: Option<SenderConfig>,
+    #[serde(default)]
+    pub message_id: Option<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Unix Time when the Email was moved into the Outbox
+    pub queued_at: u64,
+}
+
+/// Structure for the persisted Outbox
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OutboxState {
+    pub emails: Vec<OutboxEntry>,
+}
+
+//==============================================================================
+// Structure Outbox Declaration
+
+/// Structure for the Outbox shared by the Shutdown and the Startup
+#[derive(Clone)]
+pub struct Outbox {
+    config: OutboxConfig,
+    file: PathBuf,
+    state: Arc<Mutex<OutboxState>>,
+    sending: Arc<AtomicBool>,
+}
+
+//==============================================================================
+// Structure OutboxEntry Implementation
+
+impl OutboxEntry {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_email(email: EmailData) -> Self {
+        Self {
+            job: email.job.clone(),
+            request_id: email.request_id.clone(),
+            route: email.route.clone(),
+            deliver_to: email.deliver_to.clone(),
+            sender: email.sender.clone(),
+            message_id: email.message_id.clone(),
+            references: email.references.clone(),
+            queued_at: store::unix_time(),
+            email,
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn into_email(self) -> EmailData {
+        EmailData {
+            job: self.job,
+            request_id: self.request_id,
+            route: self.route,
+            deliver_to: self.deliver_to,
+            sender: self.sender,
+            message_id: self.message_id,
+            references: self.references,
+            ..self.email
+        }
+    }
+}
+
+//==============================================================================
+// Structure Outbox Implementation
+
+impl Outbox {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Loads the Emails of the last Shutdown from the Data Directory
+    pub fn from_config(config: &OutboxConfig, data_directory: &str) -> Result<Self, Error> {
+        let file = store::data_file(data_directory, OUTBOX_FILE);
+        let state: OutboxState = if config.enabled {
+            store::load(&file)?
+        } else {
+            OutboxState::default()
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            file,
+            state: Arc::new(Mutex::new(state)),
+            sending: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Keeps the Emails in the Outbox File
+    /// Emails whose Job is in the Outbox already are not added again because they were
+    /// in flight while the Outbox was sent again
+    /// Returns the Count of kept Emails which is 0 when the Outbox is disabled
+    pub fn store(&self, emails: Vec<EmailData>) -> Result<usize, Error> {
+        if !self.config.enabled || emails.is_empty() {
+            return Ok(0);
+        }
+
+        let count = emails.len();
+        let mut state = self.lock();
+        let added: Vec<OutboxEntry> = emails
+            .into_iter()
+            .filter(|email| {
+                email.job.is_empty() || !state.emails.iter().any(|entry| entry.job == email.job)
+            })
+            .map(OutboxEntry::from_email)
+            .collect();
+
+        if !added.is_empty() {
+            state.emails.extend(added);
+            store::save(&self.file, &*state)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Removes all Emails from the Outbox
+    pub fn take(&self) -> Result<Vec<EmailData>, Error> {
+        if !self.config.enabled {
+            return Ok(Vec::new());
+        }
+
+        let mut state = self.lock();
+        let emails: Vec<OutboxEntry> = state.emails.drain(..).collect();
+
+        if !emails.is_empty() {
+            store::save(&self.file, &*state)?;
+        }
+
+        Ok(emails.into_iter().map(OutboxEntry::into_email).collect())
+    }
+
+    /// Removes the first Email of the Job from the Outbox
+    fn remove(&self, job: &str) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        if let Some(position) = state.emails.iter().position(|entry| entry.job == job) {
+            state.emails.remove(position);
+            store::save(&self.file, &*state)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends the kept Emails again
+    /// Each Email is removed from the Outbox only after it was sent
+    /// so that the Emails which fail again are still kept when the Service stops
+    pub async fn resend(self, link: EmailLink) {
+        // Only one Resend runs at a Time so that no Email is sent twice
+        if self.sending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let emails = self.emails();
+
+        if !emails.is_empty() {
+            info!("Outbox: sending {} Emails again ...", emails.len());
+
+            let total = emails.len();
+            let mut kept = 0;
+
+            for email in emails {
+                let sent = match link.send_email(email.clone()).await {
+                    // The open Circuit has left the Email in the Outbox
+                    Ok(rs) if rs.status == "queued" => {
+                        kept += 1;
+                        false
+                    }
+                    Ok(_) => true,
+                    // The Email Worker can still send the Email so it must not be sent again
+                    Err(e) if e.kind() == EmailErrorKind::Timeout => {
+                        warn!("Outbox: Job '{}' was not answered: '{:?}'", email.job, e);
+                        true
+                    }
+                    Err(e) => {
+                        warn!("Outbox: Job '{}' failed again: '{:?}'", email.job, e);
+                        kept += 1;
+                        false
+                    }
+                };
+
+                if sent {
+                    if let Err(e) = self.remove(&email.job) {
+                        error!("Outbox: Job '{}' could not be removed: '{}'", email.job, e);
+                    }
+                }
+            }
+
+            info!(
+                "Outbox: {} Emails sent again; {} kept in the Outbox",
+                total - kept,
+                kept
+            );
+        }
+
+        self.sending.store(false, Ordering::SeqCst);
+    }
+
+    /// Sends the kept Emails again every `interval` when the Circuit lets Emails through
+    /// and the Mailbox of the Email Workers is not full
+    pub async fn watch(self, link: EmailLink, interval: Duration) {
+        loop {
+            actix_rt::time::delay_for(interval).await;
+
+            let ready = link.has_capacity()
+                && link
+                    .circuit()
+                    .map(|circuit| circuit.status().retry_after == 0)
+                    .unwrap_or(true);
+
+            if ready && !self.is_empty() {
+                self.clone().resend(link.clone()).await;
+            }
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Copies the kept Emails without removing them
+    pub fn emails(&self) -> Vec<EmailData> {
+        self.lock()
+            .emails
+            .iter()
+            .cloned()
+            .map(OutboxEntry::into_email)
+            .collect()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().emails.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().emails.is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, OutboxState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
@@ -0,0 +1,335 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Routing Tree
+
+* This Module defines the Routing Tree that selects Recipients, Templates
+* and the Sender Identity for each incoming Email
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "regex" must be installed
+*/
+
+use std::borrow::Cow;
+
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{MatcherConfig, RouteConfig, RoutingConfig, SenderConfig, ValidationConfig};
+use crate::email::EmailData;
+
+//==============================================================================
+// Structure Router Declaration
+
+/// Structure for the compiled Routing Tree
+/// The Tree is walked from the Root Route into the first Child Route that matches
+/// until no further Child matches. Settings that a Route does not define
+/// are inherited from its Parent Route.
+#[derive(Debug, Clone)]
+pub struct Router {
+    enabled: bool,
+    root: Route,
+    validation: ValidationConfig,
+}
+
+#[derive(Debug, Clone)]
+struct Route {
+    config: RouteConfig,
+    matchers: Vec<Matcher>,
+    routes: Vec<Route>,
+}
+
+/// Structure for a compiled Matcher on a Field or a Label of an Email
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    name: String,
+    value: String,
+    regex: Option<Regex>,
+}
+
+/// Structure for the Result of the Routing of an Email
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RouteResult {
+    pub route: String,
+    pub path: Vec<String>,
+    pub recipients: Vec<String>,
+    pub sender: Option<SenderConfig>,
+    pub subject: String,
+    pub message: String,
+    pub digest: bool,
+}
+
+//==============================================================================
+// Structure Router Implementation
+
+impl Router {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &RoutingConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            root: Route::from_config(&config.root),
+            validation: ValidationConfig::new(),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Sets the Limits which the rendered Subject is truncated to
+    pub fn set_validation(&mut self, config: &ValidationConfig) {
+        self.validation = config.clone();
+    }
+
+    /// Routes the Email and applies the Settings of the matching Route to it
+    pub fn apply(&self, email: &mut EmailData) -> Option<RouteResult> {
+        let result = self.resolve(email)?;
+
+        email.subject = result.subject.clone();
+        email.message = result.message.clone();
+        email.digest = result.digest;
+        email.route = Some(result.route.clone());
+        email.deliver_to = result.recipients.clone();
+        email.sender = result.sender.clone();
+
+        Some(result)
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Finds the Route for the Email without modifying it
+    pub fn resolve(&self, email: &EmailData) -> Option<RouteResult> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut result = RouteResult {
+            subject: email.subject.clone(),
+            message: email.message.clone(),
+            digest: email.digest,
+            ..Default::default()
+        };
+        let mut subject_template = String::new();
+        let mut message_template = String::new();
+        let mut route = Some(&self.root);
+
+        while let Some(current) = route {
+            let config = &current.config;
+
+            result.route = config.name.clone();
+            result.path.push(config.name.clone());
+
+            if !config.recipients.is_empty() {
+                result.recipients = config.recipients.clone();
+            }
+
+            if config.sender.is_some() {
+                result.sender = config.sender.clone();
+            }
+
+            if !config.subject_template.is_empty() {
+                subject_template = config.subject_template.clone();
+            }
+
+            if !config.message_template.is_empty() {
+                message_template = config.message_template.clone();
+            }
+
+            if let Some(digest) = config.digest {
+                result.digest = digest;
+            }
+
+            route = current.routes.iter().find(|child| child.matches(email));
+        }
+
+        if !subject_template.is_empty() {
+            result.subject =
+                render_subject(&subject_template, email, &result.route, &self.validation);
+        }
+
+        if !message_template.is_empty() {
+            result.message = render_template(&message_template, email, &result.route);
+        }
+
+        Some(result)
+    }
+}
+
+//==============================================================================
+// Structure Route Implementation
+
+impl Route {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    fn from_config(config: &RouteConfig) -> Self {
+        Self {
+            config: RouteConfig {
+                routes: Vec::new(),
+                ..config.clone()
+            },
+            matchers: config.matchers.iter().map(Matcher::from_config).collect(),
+            routes: config.routes.iter().map(Route::from_config).collect(),
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    fn matches(&self, email: &EmailData) -> bool {
+        self.matchers.iter().all(|matcher| matcher.matches(email))
+    }
+}
+
+//==============================================================================
+// Structure Matcher Implementation
+
+impl Matcher {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &MatcherConfig) -> Self {
+        let regex = if config.regex.is_empty() {
+            None
+        } else {
+            match Regex::new(&config.regex) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    warn!(
+                        "Routing Config: Matcher '{}': Regex '{}' is invalid: '{}'",
+                        config.name, config.regex, e
+                    );
+                    // An invalid Regex must never match
+                    Regex::new("$^").ok()
+                }
+            }
+        };
+
+        Self {
+            name: config.name.clone(),
+            value: config.value.clone(),
+            regex,
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    pub fn matches(&self, email: &EmailData) -> bool {
+        let field = field_value(email, &self.name).unwrap_or_default();
+
+        match &self.regex {
+            Some(regex) => regex.is_match(&field),
+            None => field == self.value.as_str(),
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Looks up a Field of the Email, its Fingerprint or one of its Labels
+fn field_value<'a>(email: &'a EmailData, name: &str) -> Option<Cow<'a, str>> {
+    match name {
+        "subject" => Some(Cow::Borrowed(email.subject.as_str())),
+        "from" => Some(Cow::Borrowed(email.from.as_str())),
+        "to" => Some(Cow::Borrowed(email.to.as_str())),
+        "message" => Some(Cow::Borrowed(email.message.as_str())),
+        "fingerprint" => Some(Cow::Owned(email.fingerprint_key())),
+        _ => email
+            .labels
+            .get(name)
+            .map(|value| Cow::Borrowed(value.as_str())),
+    }
+}
+
+/// Replaces the Placeholders `{subject}`, `{message}`, `{from}`, `{to}`, `{route}`
+/// and `{labels.<name>}` in the Template
+/// The Template is scanned once so that Placeholders within the Values are not expanded.
+/// Unknown Placeholders are kept as they are.
+fn render_template(template: &str, email: &EmailData, route: &str) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+        let value = match &rest[1..end] {
+            "subject" => Some(email.subject.as_str()),
+            "message" => Some(email.message.as_str()),
+            "from" => Some(email.from.as_str()),
+            "to" => Some(email.to.as_str()),
+            "route" => Some(route),
+            name => name
+                .strip_prefix("labels.")
+                .and_then(|label| email.labels.get(label))
+                .map(String::as_str),
+        };
+
+        match value {
+            Some(value) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                // The Brace is kept and the Scan resumes after it
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Renders the Subject Template as a single Header Line
+/// Line Breaks and Control Characters are replaced by Spaces and the Subject is truncated
+/// to the `max_subject_length` and the `max_header_length` of the Validation
+fn render_subject(
+    template: &str,
+    email: &EmailData,
+    route: &str,
+    config: &ValidationConfig,
+) -> String {
+    let rendered = render_template(template, email, route);
+    let mut subject = String::with_capacity(rendered.len());
+
+    for (count, c) in rendered
+        .chars()
+        .map(|c| if c.is_control() && c != '\t' { ' ' } else { c })
+        .enumerate()
+    {
+        if count >= config.max_subject_length
+            || subject.len() + c.len_utf8() > config.max_header_length
+        {
+            warn!(
+                "Route '{}': rendered Subject was truncated to {} Characters",
+                route, count
+            );
+            break;
+        }
+
+        subject.push(c);
+    }
+
+    subject
+}
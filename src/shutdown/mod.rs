@@ -0,0 +1,168 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Shutdown
+
+* This Module defines the Shutdown Sequence which stops the HTTP Server on SIGTERM or SIGINT
+* and awaits the queued and sending Emails until the Deadline
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::io::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use actix_rt::signal::unix::{signal, SignalKind};
+use actix_web::dev::Server;
+use futures_util::future::select;
+use log::{error, info, log, Level};
+
+use crate::config::ShutdownConfig;
+use crate::email::EmailLink;
+use crate::outbox::Outbox;
+
+//==============================================================================
+// Structure ShutdownClock Declaration
+
+/// Structure for the Time when the Shutdown was requested
+#[derive(Clone, Default)]
+pub struct ShutdownClock {
+    started: Arc<Mutex<Option<Instant>>>,
+}
+
+/// Structure for the Outcome of the Shutdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShutdownSummary {
+    /// Emails which were queued or sending when the HTTP Server was stopped
+    pub in_flight: usize,
+    /// Emails which were answered before the Deadline
+    pub drained: usize,
+    /// Emails which were kept in the Outbox
+    pub moved: usize,
+    /// Emails which were given up
+    pub lost: usize,
+    pub duration: Duration,
+}
+
+//==============================================================================
+// Structure ShutdownClock Implementation
+
+impl ShutdownClock {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Records the Start of the Shutdown unless it was already started
+    pub fn start(&self) {
+        let mut started = match self.started.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        };
+
+        started.get_or_insert_with(Instant::now);
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Returns the Start of the Shutdown or now if the Server stopped by itself
+    pub fn started(&self) -> Instant {
+        let started = match self.started.lock() {
+            Ok(lock) => *lock,
+            Err(e) => *e.into_inner(),
+        };
+
+        started.unwrap_or_else(Instant::now)
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Spawns a Task that stops the HTTP Server gracefully on SIGTERM or SIGINT
+/// The Server stops accepting Connections and awaits the running Requests
+pub fn stop_on_signal(server: Server) -> Result<ShutdownClock, Error> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let clock = ShutdownClock::new();
+    let started = clock.clone();
+
+    actix_rt::spawn(async move {
+        select(Box::pin(terminate.recv()), Box::pin(interrupt.recv())).await;
+
+        started.start();
+        info!("Shutdown: stop accepting Requests ...");
+
+        server.stop(true).await;
+    });
+
+    Ok(clock)
+}
+
+/// Awaits the queued and sending Emails until `drain_timeout` Seconds after the Start
+/// The remaining Emails are kept in the Outbox when it is enabled
+pub async fn drain(
+    link: &EmailLink,
+    outbox: &Outbox,
+    config: &ShutdownConfig,
+    started: Instant,
+) -> ShutdownSummary {
+    let deadline = started + Duration::from_secs(config.drain_timeout);
+    let in_flight = link.in_flight().len();
+
+    if in_flight > 0 {
+        info!(
+            "Shutdown: awaiting {} Emails for up to {}s ...",
+            in_flight,
+            deadline.saturating_duration_since(Instant::now()).as_secs()
+        );
+    }
+
+    let remaining = link.in_flight().drain(deadline).await;
+    let left = remaining.len();
+    let moved = match outbox.store(remaining) {
+        Ok(moved) => moved,
+        Err(e) => {
+            error!("Shutdown: Emails could not be kept in the Outbox: '{}'", e);
+            0
+        }
+    };
+    let summary = ShutdownSummary {
+        in_flight,
+        drained: in_flight.saturating_sub(left),
+        moved,
+        lost: left - moved,
+        duration: started.elapsed(),
+    };
+
+    // Lost Emails are reported as Warning
+    let level = if summary.lost > 0 {
+        Level::Warn
+    } else {
+        Level::Info
+    };
+
+    log!(
+        level,
+        "Shutdown: finished in {:.3}s; {} of {} Emails drained; {} moved to the Outbox; {} lost",
+        summary.duration.as_secs_f64(),
+        summary.drained,
+        summary.in_flight,
+        summary.moved,
+        summary.lost
+    );
+
+    summary
+}
@@ -0,0 +1,325 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Silences
+
+* This Module defines the Silences that mute matching Alerts for a Period
+* and their Persistence in the Data Directory
+*
+*---------------------------------
+* Requirements:
+*/
+
+use std::collections::VecDeque;
+use std::io::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{error, info};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::MatcherConfig;
+use crate::email::{self, EmailData, EmailResponse};
+use crate::routing::Matcher;
+use crate::store;
+
+const SILENCE_FILE: &str = "silences.json";
+
+/// Count of suppressed Emails that are kept as Record
+const MAX_SUPPRESSED: usize = 1000;
+
+/// Interval in which the Records of suppressed Emails are saved
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+//==============================================================================
+// Structure Silence Declaration
+
+/// Structure for a Silence
+/// All Matchers must match for an Email to be silenced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Silence {
+    pub id: String,
+    pub matchers: Vec<MatcherConfig>,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub created_by: String,
+    pub comment: String,
+    // The Matchers are compiled once when the Silence is created or loaded
+    #[serde(skip)]
+    compiled: Vec<Matcher>,
+}
+
+/// Structure for the Request to create a Silence
+/// Without `starts_at` the Silence starts immediately and without `ends_at`
+/// it lasts for `duration` Seconds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SilenceRequest {
+    pub matchers: Vec<MatcherConfig>,
+    #[serde(default)]
+    pub starts_at: Option<u64>,
+    #[serde(default)]
+    pub ends_at: Option<u64>,
+    #[serde(default)]
+    pub duration: Option<u64>,
+    pub created_by: String,
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// Structure for the Record of a suppressed Email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressedEmail {
+    pub silence: String,
+    pub job: String,
+    pub subject: String,
+    pub recipients: Vec<String>,
+    pub suppressed_at: u64,
+}
+
+/// Structure for the persisted State of the Silences
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SilenceState {
+    pub silences: Vec<Silence>,
+    pub suppressed: VecDeque<SuppressedEmail>,
+}
+
+//==============================================================================
+// Structure SilenceStore Declaration
+
+/// Structure for the shared Silences of all HTTP Workers
+#[derive(Clone)]
+pub struct SilenceStore {
+    file: PathBuf,
+    state: Arc<Mutex<SilenceState>>,
+    dirty: Arc<AtomicBool>,
+}
+
+//==============================================================================
+// Structure Silence Implementation
+
+impl Silence {
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    fn compile(&mut self) {
+        self.compiled = self.matchers.iter().map(Matcher::from_config).collect();
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Tells whether all Matchers of the Silence match the Email
+    pub fn matches(&self, email: &EmailData) -> bool {
+        self.compiled.iter().all(|m| m.matches(email))
+    }
+}
+
+//==============================================================================
+// Structure SilenceStore Implementation
+
+impl SilenceStore {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Loads the Silences from the Data Directory
+    pub fn from_directory(data_directory: &str) -> Result<Self, Error> {
+        let file = store::data_file(data_directory, SILENCE_FILE);
+        let mut state: SilenceState = store::load(&file)?;
+
+        for silence in state.silences.iter_mut() {
+            silence.compile();
+        }
+
+        info!(
+            "Silences: {} Silences loaded from {:?}",
+            state.silences.len(),
+            file
+        );
+
+        Ok(Self {
+            file,
+            state: Arc::new(Mutex::new(state)),
+            dirty: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Creates a new Silence from the Request
+    pub fn add(&self, request: SilenceRequest) -> Result<Silence, String> {
+        if request.matchers.is_empty() {
+            return Err(String::from("Silence needs at least one Matcher"));
+        }
+
+        if request.created_by.trim().is_empty() {
+            return Err(String::from("Silence needs a Creator"));
+        }
+
+        // An invalid Regex would silently never match
+        for matcher in request.matchers.iter().filter(|m| !m.regex.is_empty()) {
+            if let Err(e) = Regex::new(&matcher.regex) {
+                return Err(format!(
+                    "Matcher '{}': Regex '{}' is invalid: '{}'",
+                    matcher.name, matcher.regex, e
+                ));
+            }
+        }
+
+        let starts_at = request.starts_at.unwrap_or_else(store::unix_time);
+        let ends_at = match (request.ends_at, request.duration) {
+            (Some(ends_at), _) => ends_at,
+            (None, Some(duration)) => starts_at
+                .checked_add(duration)
+                .ok_or_else(|| String::from("Silence Duration is too long"))?,
+            (None, None) => return Err(String::from("Silence needs an End Time or a Duration")),
+        };
+
+        if ends_at <= starts_at {
+            return Err(String::from("Silence must end after its Start"));
+        }
+
+        let mut silence = Silence {
+            id: email::new_job_id(),
+            matchers: request.matchers,
+            starts_at,
+            ends_at,
+            created_by: request.created_by,
+            comment: request.comment,
+            compiled: Vec::new(),
+        };
+
+        silence.compile();
+
+        let now = store::unix_time();
+        let mut state = self.lock();
+
+        // Expired Silences are removed when a new Silence is added
+        state.silences.retain(|s| now < s.ends_at);
+        state.silences.push(silence.clone());
+        self.persist(&state);
+
+        Ok(silence)
+    }
+
+    /// Removes the Silence with the Identifier
+    pub fn remove(&self, id: &str) -> Option<Silence> {
+        let mut state = self.lock();
+        let index = state.silences.iter().position(|s| s.id == id)?;
+        let silence = state.silences.remove(index);
+
+        self.persist(&state);
+
+        Some(silence)
+    }
+
+    /// Checks the Email against the active Silences
+    /// A silenced Email is recorded and gets a `silenced` Response
+    /// The Records are saved by `watch()` and not with each Email
+    pub fn check(&self, email: &EmailData) -> Option<EmailResponse> {
+        let now = store::unix_time();
+        let mut state = self.lock();
+        let silence = state
+            .silences
+            .iter()
+            .filter(|s| s.starts_at <= now && now < s.ends_at)
+            .find(|s| s.matches(email))?
+            .id
+            .clone();
+
+        if state.suppressed.len() >= MAX_SUPPRESSED {
+            state.suppressed.pop_front();
+        }
+
+        state.suppressed.push_back(SuppressedEmail {
+            silence: silence.clone(),
+            job: email.job.clone(),
+            subject: email.subject.clone(),
+            recipients: email.delivery_recipients(),
+            suppressed_at: now,
+        });
+        self.dirty.store(true, Ordering::Relaxed);
+
+        Some(EmailResponse {
+            status: String::from("silenced"),
+            report: format!("Email was suppressed by Silence '{}'", silence),
+            job: email.job.clone(),
+        })
+    }
+
+    /// Saves the State if Emails were suppressed since the last Save
+    pub fn flush(&self) {
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            let state = self.lock();
+
+            self.persist(&state);
+        }
+    }
+
+    /// Saves the Records of the suppressed Emails every `interval`
+    pub async fn watch(self, interval: Duration) {
+        loop {
+            actix_rt::time::delay_for(interval).await;
+
+            self.flush();
+        }
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Returns the Identifier of the active Silence which matches the Email
+    /// The Email is not recorded as suppressed
+    pub fn silenced_by(&self, email: &EmailData) -> Option<String> {
+        let now = store::unix_time();
+
+        self.lock()
+            .silences
+            .iter()
+            .filter(|s| s.starts_at <= now && now < s.ends_at)
+            .find(|s| s.matches(email))
+            .map(|s| s.id.clone())
+    }
+
+    /// Returns the Silences which have not expired yet
+    pub fn list(&self) -> Vec<Silence> {
+        let now = store::unix_time();
+
+        self.lock()
+            .silences
+            .iter()
+            .filter(|s| now < s.ends_at)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the Records of the suppressed Emails
+    pub fn suppressed(&self) -> Vec<SuppressedEmail> {
+        self.lock().suppressed.iter().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SilenceState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+
+    fn persist(&self, state: &SilenceState) {
+        // The pending Records are saved with the State
+        self.dirty.store(false, Ordering::Relaxed);
+
+        if let Err(e) = store::save(&self.file, state) {
+            error!("Silences: save to {:?} failed: '{}'", self.file, e);
+        }
+    }
+}
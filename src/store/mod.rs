@@ -0,0 +1,88 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Local Store
+
+* This Module defines functions to persist the Service State as JSON Files
+* in the Data Directory
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "serde-json" must be installed
+*/
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Returns the current Time as Unix Timestamp in Seconds
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Builds the Path of a State File in the Data Directory
+pub fn data_file(data_directory: &str, name: &str) -> PathBuf {
+    let mut path = PathBuf::from(data_directory);
+
+    path.push(name);
+
+    path
+}
+
+/// Loads the State from its File
+/// A missing File yields the Default State
+pub fn load<T: DeserializeOwned + Default>(file: &Path) -> Result<T, Error> {
+    if !file.exists() {
+        return Ok(T::default());
+    }
+
+    let content = fs::read_to_string(file).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!(
+                "State File {:?}: read file failed with Error: '{:?}'",
+                file, e
+            ),
+        )
+    })?;
+
+    serde_json::from_str(&content).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "State File {:?}: parse file failed with Error: '{:?}'",
+                file, e
+            ),
+        )
+    })
+}
+
+/// Saves the State into its File
+/// The State is written to a temporary File first which then replaces the old File
+pub fn save<T: Serialize>(file: &Path, state: &T) -> Result<(), Error> {
+    if let Some(directory) = file.parent() {
+        if !directory.as_os_str().is_empty() {
+            fs::create_dir_all(directory)?;
+        }
+    }
+
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{:?}", e)))?;
+    let mut temp_file = file.to_path_buf();
+
+    temp_file.set_extension("tmp");
+
+    fs::write(&temp_file, content)?;
+    fs::rename(&temp_file, file)
+}
@@ -0,0 +1,268 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Thread Store
+
+* This Module defines the Store that assigns Message-IDs and groups the Emails
+* of the same Alert into one Thread
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "sha2" must be installed
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::error;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::ThreadingConfig;
+use crate::email::EmailData;
+use crate::store;
+
+const THREAD_FILE: &str = "threads.json";
+
+/// Interval in which the changed Threads are saved
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+//==============================================================================
+// Structure ThreadEntry Declaration
+
+/// Structure for a Thread of Emails of the same Alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadEntry {
+    /// Message-ID of the first Email which is the Root of the Thread
+    pub first_message_id: String,
+    /// Message-ID of the latest Email which the next Email replies to
+    pub last_message_id: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub count: u64,
+}
+
+/// Structure for the persisted Threads
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ThreadState {
+    pub threads: BTreeMap<String, ThreadEntry>,
+    /// The Keys of the Threads ordered by their last Update for the Eviction
+    #[serde(skip)]
+    recent: BTreeSet<(u64, String)>,
+}
+
+//==============================================================================
+// Structure ThreadStore Declaration
+
+/// Structure for the shared Threads of all HTTP Workers
+#[derive(Clone)]
+pub struct ThreadStore {
+    config: ThreadingConfig,
+    domain: String,
+    file: PathBuf,
+    state: Arc<Mutex<ThreadState>>,
+    dirty: Arc<AtomicBool>,
+}
+
+//==============================================================================
+// Structure ThreadStore Implementation
+
+impl ThreadStore {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    /// Loads the Threads from the Data Directory
+    /// Without a configured Domain the Domain of the Sender Address is used
+    pub fn from_config(
+        config: &ThreadingConfig,
+        sender_address: &str,
+        data_directory: &str,
+    ) -> Result<Self, Error> {
+        let file = store::data_file(data_directory, THREAD_FILE);
+        let mut state: ThreadState = store::load(&file)?;
+
+        state.recent = state
+            .threads
+            .iter()
+            .map(|(key, thread)| (thread.updated_at, key.clone()))
+            .collect();
+
+        let domain = if config.domain.is_empty() {
+            match sender_address.rsplit_once('@') {
+                Some((_, domain)) if !domain.trim().is_empty() => domain.trim().to_owned(),
+                _ => String::from("localhost"),
+            }
+        } else {
+            config.domain.clone()
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            domain,
+            file,
+            state: Arc::new(Mutex::new(state)),
+            dirty: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Assigns a new Message-ID to the Email and links it to the earlier Emails of its Thread
+    /// The Threads are persisted by `watch()` so that they survive Restarts
+    pub fn assign(&self, email: &mut EmailData) {
+        let key = thread_key(email);
+
+        self.assign_key(email, key);
+    }
+
+    /// Assigns the Email to the Thread of the `key` which was taken from `thread_key()`
+    /// before the Email was changed
+    pub fn assign_key(&self, email: &mut EmailData, key: String) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let message_id = format!("<{}.{}@{}>", email.job, &key[..16], self.domain);
+        let now = store::unix_time();
+        let mut guard = self.lock();
+        let state = &mut *guard;
+
+        match state.threads.get_mut(&key) {
+            Some(thread) => {
+                email.references = vec![thread.first_message_id.clone()];
+
+                if thread.last_message_id != thread.first_message_id {
+                    email.references.push(thread.last_message_id.clone());
+                }
+
+                state.recent.remove(&(thread.updated_at, key.clone()));
+                state.recent.insert((now, key));
+
+                thread.last_message_id = message_id.clone();
+                thread.updated_at = now;
+                thread.count += 1;
+            }
+            None => {
+                // The least recently used Threads are forgotten first
+                while self.config.max_threads > 0 && state.threads.len() >= self.config.max_threads
+                {
+                    let oldest = match state.recent.iter().next() {
+                        Some(oldest) => oldest.clone(),
+                        None => break,
+                    };
+
+                    state.recent.remove(&oldest);
+                    state.threads.remove(&oldest.1);
+                }
+
+                state.recent.insert((now, key.clone()));
+                state.threads.insert(
+                    key,
+                    ThreadEntry {
+                        first_message_id: message_id.clone(),
+                        last_message_id: message_id.clone(),
+                        created_at: now,
+                        updated_at: now,
+                        count: 1,
+                    },
+                );
+            }
+        }
+
+        email.message_id = Some(message_id);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Saves the Threads if they changed since the last Save
+    pub fn flush(&self) {
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            let state = self.lock();
+
+            if let Err(e) = store::save(&self.file, &*state) {
+                error!("Threads: save to {:?} failed: '{}'", self.file, e);
+            }
+        }
+    }
+
+    /// Saves the changed Threads every `interval`
+    pub async fn watch(self, interval: Duration) {
+        loop {
+            actix_rt::time::delay_for(interval).await;
+
+            self.flush();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ThreadState> {
+        match self.state.lock() {
+            Ok(lock) => lock,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Identifies the Thread by the Caller supplied `thread_id` or else by the `alert_key()`
+/// The Key is hashed to a hex String.
+pub fn thread_key(email: &EmailData) -> String {
+    match &email.thread_id {
+        Some(thread_id) if !thread_id.trim().is_empty() => {
+            let mut hasher = Sha256::new();
+
+            hasher.update(b"thread:");
+            hasher.update(thread_id.trim().as_bytes());
+
+            hex::encode(hasher.finalize())
+        }
+        _ => alert_key(email),
+    }
+}
+
+/// Identifies the Alert across its Status Changes by the `fingerprint`
+/// or by the Subject without the Grafana Status Prefix like `[FIRING:2]` or `[RESOLVED]`
+/// and the Recipients. It is shared by the Email Threads and the Escalations.
+pub fn alert_key(email: &EmailData) -> String {
+    let mut hasher = Sha256::new();
+
+    match &email.fingerprint {
+        Some(fingerprint) if !fingerprint.trim().is_empty() => {
+            hasher.update(b"fingerprint:");
+            hasher.update(fingerprint.trim().as_bytes());
+        }
+        _ => {
+            hasher.update(b"subject:");
+            hasher.update(strip_status(&email.subject).as_bytes());
+            hasher.update(b"\n");
+            hasher.update(email.recipients().join(",").to_lowercase().as_bytes());
+        }
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+/// Removes the leading Status Tag of a Grafana Subject
+fn strip_status(subject: &str) -> &str {
+    let subject = subject.trim();
+
+    if subject.starts_with('[') {
+        if let Some(end) = subject.find(']') {
+            let tag = subject[1..end].to_uppercase();
+
+            if tag.starts_with("FIRING") || tag.starts_with("RESOLVED") {
+                return subject[end + 1..].trim_start();
+            }
+        }
+    }
+
+    subject
+}
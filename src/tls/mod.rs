@@ -0,0 +1,281 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage TLS Listener
+
+* This Module defines the TLS Configuration for the HTTPS Listener
+* and the Reloading of the Certificates on SIGHUP
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "rustls" must be installed
+* - The Rust Crate "webpki" must be installed
+*/
+
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind};
+use std::sync::{Arc, RwLock};
+
+use actix_rt::signal::unix::{signal, SignalKind};
+use log::{error, info};
+use rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls::sign::{any_supported_type, CertifiedKey};
+use rustls::{
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, Certificate,
+    ClientCertVerified, ClientCertVerifier, ClientHello, DistinguishedNames, NoClientAuth,
+    PrivateKey, ResolvesServerCert, RootCertStore, ServerConfig, TLSError,
+};
+use webpki::DNSName;
+
+use super::config::TLSConfig;
+
+//==============================================================================
+// Structure CertificateResolver Declaration
+
+/// Structure to serve the current Certificate Chain
+/// The Certificate Chain, the Key and the Client CA can be replaced while the Server is running
+pub struct CertificateResolver {
+    config: TLSConfig,
+    key: RwLock<CertifiedKey>,
+    client: Option<Arc<ClientVerifier>>,
+}
+
+/// Structure to verify Client Certificates against the current Client CA
+pub struct ClientVerifier {
+    verifier: RwLock<Arc<dyn ClientCertVerifier>>,
+}
+
+//==============================================================================
+// Structure CertificateResolver Implementation
+
+impl CertificateResolver {
+    /*----------------------------------------------------------------------------
+     * Constructors
+     */
+
+    pub fn from_config(config: &TLSConfig) -> Result<Self, Error> {
+        let client = if config.client_ca.is_empty() {
+            None
+        } else {
+            Some(Arc::new(ClientVerifier {
+                verifier: RwLock::new(load_client_verifier(config)?),
+            }))
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            key: RwLock::new(load_certified_key(config)?),
+            client,
+        })
+    }
+
+    /*----------------------------------------------------------------------------
+     * Administration Methods
+     */
+
+    /// Reads the Certificate Chain, the Key and the Client CA again from their Files
+    /// On Failure the current Certificates remain in use
+    pub fn reload(&self) -> Result<(), Error> {
+        let key = load_certified_key(&self.config)?;
+        let verifier = match &self.client {
+            Some(_) => Some(load_client_verifier(&self.config)?),
+            None => None,
+        };
+
+        match self.key.write() {
+            Ok(mut current) => *current = key,
+            Err(e) => return Err(poisoned(e)),
+        }
+
+        if let (Some(client), Some(verifier)) = (&self.client, verifier) {
+            match client.verifier.write() {
+                Ok(mut current) => *current = verifier,
+                Err(e) => return Err(poisoned(e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /*----------------------------------------------------------------------------
+     * Consultation Methods
+     */
+
+    /// Returns the Certificate Chain and the Key which are currently served
+    pub fn certified_key(&self) -> Option<CertifiedKey> {
+        self.key.read().ok().map(|key| key.clone())
+    }
+
+    /// Returns the Verifier of the Client Certificates if a `client_ca` File is configured
+    pub fn client_verifier(&self) -> Option<Arc<ClientVerifier>> {
+        self.client.clone()
+    }
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<CertifiedKey> {
+        self.certified_key()
+    }
+}
+
+//==============================================================================
+// Structure ClientVerifier Implementation
+
+impl ClientVerifier {
+    fn current(&self) -> Option<Arc<dyn ClientCertVerifier>> {
+        self.verifier.read().ok().map(|verifier| verifier.clone())
+    }
+}
+
+impl ClientCertVerifier for ClientVerifier {
+    fn client_auth_mandatory(&self, sni: Option<&DNSName>) -> Option<bool> {
+        self.current()
+            .and_then(|verifier| verifier.client_auth_mandatory(sni))
+    }
+
+    fn client_auth_root_subjects(&self, sni: Option<&DNSName>) -> Option<DistinguishedNames> {
+        self.current()
+            .and_then(|verifier| verifier.client_auth_root_subjects(sni))
+    }
+
+    fn verify_client_cert(
+        &self,
+        presented_certs: &[Certificate],
+        sni: Option<&DNSName>,
+    ) -> Result<ClientCertVerified, TLSError> {
+        match self.current() {
+            Some(verifier) => verifier.verify_client_cert(presented_certs, sni),
+            None => Err(TLSError::General(String::from(
+                "Client CA Lock is poisoned",
+            ))),
+        }
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Builds the TLS Server Configuration for the HTTPS Listener
+/// With a configured `client_ca` File Client Certificates are verified against it.
+/// They are only required from every Client with `require_client_cert`
+pub fn server_config(
+    config: &TLSConfig,
+    resolver: Arc<CertificateResolver>,
+) -> Result<ServerConfig, Error> {
+    let mut server_config = match resolver.client_verifier() {
+        Some(client) => ServerConfig::new(client),
+        None if config.require_client_cert => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Client Certificates are required but no Client CA File is configured",
+            ))
+        }
+        None => ServerConfig::new(NoClientAuth::new()),
+    };
+
+    server_config.cert_resolver = resolver;
+
+    Ok(server_config)
+}
+
+/// Spawns a Task that reloads the Certificates whenever the Process receives SIGHUP
+pub fn reload_on_hangup(resolver: Arc<CertificateResolver>) -> Result<(), Error> {
+    let mut hangup = signal(SignalKind::hangup())?;
+
+    actix_rt::spawn(async move {
+        while hangup.recv().await.is_some() {
+            match resolver.reload() {
+                Ok(()) => info!("TLS Certificates: reloaded."),
+                Err(e) => error!("TLS Certificates: reload failed: '{}'", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn load_certified_key(config: &TLSConfig) -> Result<CertifiedKey, Error> {
+    let chain = load_certificates(&config.certificate)?;
+    let key = load_private_key(&config.key)?;
+    let signing_key = any_supported_type(&key).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Key File '{}': Key Type is not supported", config.key),
+        )
+    })?;
+
+    Ok(CertifiedKey::new(chain, Arc::new(signing_key)))
+}
+
+fn load_client_verifier(config: &TLSConfig) -> Result<Arc<dyn ClientCertVerifier>, Error> {
+    let mut roots = RootCertStore::empty();
+
+    for cert in load_certificates(&config.client_ca)? {
+        roots.add(&cert).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Client CA File '{}': Certificate is invalid: '{:?}'",
+                    config.client_ca, e
+                ),
+            )
+        })?;
+    }
+
+    if config.require_client_cert {
+        Ok(AllowAnyAuthenticatedClient::new(roots))
+    } else {
+        Ok(AllowAnyAnonymousOrAuthenticatedClient::new(roots))
+    }
+}
+
+fn load_certificates(path: &str) -> Result<Vec<Certificate>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let chain = certs(&mut reader).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Certificate File '{}': PEM could not be parsed", path),
+        )
+    })?;
+
+    if chain.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Certificate File '{}': no Certificates found", path),
+        ));
+    }
+
+    Ok(chain)
+}
+
+pub(crate) fn load_private_key(path: &str) -> Result<PrivateKey, Error> {
+    let parse_error = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Key File '{}': PEM could not be parsed", path),
+        )
+    };
+
+    let mut keys =
+        pkcs8_private_keys(&mut BufReader::new(File::open(path)?)).map_err(|_| parse_error())?;
+
+    if keys.is_empty() {
+        keys =
+            rsa_private_keys(&mut BufReader::new(File::open(path)?)).map_err(|_| parse_error())?;
+    }
+
+    keys.into_iter().next().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Key File '{}': no Private Key found", path),
+        )
+    })
+}
+
+fn poisoned<T>(e: std::sync::PoisonError<T>) -> Error {
+    Error::new(
+        ErrorKind::Other,
+        format!("Certificate Lock is poisoned: '{}'", e),
+    )
+}
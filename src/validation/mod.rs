@@ -0,0 +1,198 @@
+/*
+* @author Bodo (Hugo) Barwich
+* @version 2026-10-18
+* @package Grafana Alerting
+* @subpackage Validation
+
+* This Module defines the Validation and Normalisation of incoming Emails
+* before any Field reaches the Email Headers
+*
+*---------------------------------
+* Requirements:
+* - The Rust Crate "unicode-normalization" must be installed
+*/
+
+use std::fmt;
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::config::ValidationConfig;
+use crate::email::{self, EmailData};
+
+//==============================================================================
+// Structure ValidationError Declaration
+
+/// Structure for the Violation of a single Field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Structure for the Response to an invalid Email
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub title: String,
+    pub statuscode: u16,
+    pub description: String,
+    pub errors: Vec<FieldError>,
+}
+
+/// Structure for all Violations found in an Email
+#[derive(Debug)]
+pub struct ValidationError {
+    pub errors: Vec<FieldError>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect();
+
+        write!(f, "Email is invalid: {}", errors.join("; "))
+    }
+}
+
+impl ResponseError for ValidationError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::UnprocessableEntity().json(ValidationReport {
+            title: String::from("Unprocessable Entity"),
+            statuscode: 422,
+            description: format!("{} Fields are invalid", self.errors.len()),
+            errors: self.errors.clone(),
+        })
+    }
+}
+
+//==============================================================================
+// Auxiliary Functions
+
+/// Normalises the Unicode of all Text Fields to NFC and checks them
+/// Every Violation is reported with its Field
+pub fn validate(email: &mut EmailData, config: &ValidationConfig) -> Result<(), ValidationError> {
+    normalise(email);
+
+    let mut errors = Vec::new();
+
+    check_header(&mut errors, "subject", &email.subject, config);
+    check_header(&mut errors, "from", &email.from, config);
+    check_header(&mut errors, "to", &email.to, config);
+
+    let subject_length = email.subject.chars().count();
+
+    if subject_length > config.max_subject_length {
+        errors.push(FieldError {
+            field: String::from("subject"),
+            message: format!(
+                "must not be longer than {} Characters but has {}",
+                config.max_subject_length, subject_length
+            ),
+        });
+    }
+
+    if let Some(reply_to) = &email.reply_to {
+        if let Err(e) = email::validate_address(reply_to) {
+            errors.push(FieldError {
+                field: String::from("reply_to"),
+                message: e,
+            });
+        }
+    }
+
+    if let Some(thread_id) = &email.thread_id {
+        check_header(&mut errors, "thread_id", thread_id, config);
+    }
+
+    if let Some(fingerprint) = &email.fingerprint {
+        check_header(&mut errors, "fingerprint", fingerprint, config);
+    }
+
+    // Labels can be rendered into the Subject by the Routing Templates
+    for (name, value) in &email.labels {
+        let field = format!("labels.{}", name.escape_debug());
+
+        check_header(&mut errors, &field, name, config);
+        check_header(&mut errors, &field, value, config);
+    }
+
+    for (name, value) in &email.headers {
+        let field = format!("headers.{}", name.escape_debug());
+
+        if !email::is_custom_header(name) {
+            errors.push(FieldError {
+                field,
+                message: String::from(
+                    "only 'X-Priority', 'Importance' and 'X-Alert-*' are allowed",
+                ),
+            });
+        } else if let Err(e) = email::validate_header(name, "") {
+            errors.push(FieldError { field, message: e });
+        } else {
+            check_header(&mut errors, &field, value, config);
+        }
+    }
+
+    if email.message.contains('\0') {
+        errors.push(FieldError {
+            field: String::from("message"),
+            message: String::from("must not contain NUL Bytes"),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError { errors })
+    }
+}
+
+/// Normalises the Text Fields to the Unicode Normal Form C
+fn normalise(email: &mut EmailData) {
+    let nfc = |text: &str| -> String { text.nfc().collect() };
+
+    email.subject = nfc(&email.subject);
+    email.from = nfc(&email.from);
+    email.to = nfc(&email.to);
+    email.message = nfc(&email.message);
+    email.reply_to = email.reply_to.as_deref().map(nfc);
+    email.thread_id = email.thread_id.as_deref().map(nfc);
+    email.fingerprint = email.fingerprint.as_deref().map(nfc);
+    email.labels = email
+        .labels
+        .iter()
+        .map(|(name, value)| (nfc(name), nfc(value)))
+        .collect();
+    email.headers = email
+        .headers
+        .iter()
+        .map(|(name, value)| (name.clone(), nfc(value)))
+        .collect();
+}
+
+/// Rejects Line Breaks, Control Characters and overlong Values in a Header Field
+fn check_header(errors: &mut Vec<FieldError>, field: &str, value: &str, config: &ValidationConfig) {
+    let message = if value.contains('\r') || value.contains('\n') {
+        String::from("must not contain Line Breaks")
+    } else if value.chars().any(|c| c.is_control() && c != '\t') {
+        String::from("must not contain Control Characters")
+    } else if value.len() > config.max_header_length {
+        format!("must not be longer than {} Bytes", config.max_header_length)
+    } else {
+        return;
+    };
+
+    errors.push(FieldError {
+        field: field.to_owned(),
+        message,
+    });
+}